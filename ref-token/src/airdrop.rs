@@ -0,0 +1,245 @@
+//! Merkle airdrop campaigns.
+//!
+//! Owner registers a campaign with the merkle root of its `(index, account_id, amount)` entries,
+//! escrowing the total amount from owner's balance into the contract account,
+//! and paying with attached NEAR for the storage of the claimed bitmap over entry indexes.
+//! Each account claims its own entry with a proof, the bitmap prevents double claims,
+//! and owner reclaims what is left unclaimed after the campaign expires,
+//! which also clears the bitmap and refunds its storage.
+//!
+//! Leaf of an entry is `sha256(0x00 ++ index as u32 LE bytes ++ amount as u128 LE bytes ++ account_id bytes)`,
+//! and each level hashes a pair as `sha256(0x01 ++ min(a, b) ++ max(a, b))`, so a proof is just the sibling hashes.
+//! The prefixes keep an internal node from passing as a leaf.
+
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::Promise;
+
+const HASH_LEN: usize = 32;
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+/// Number of entries tracked in a word of the claimed bitmap.
+const BITMAP_WORD_BITS: u32 = 128;
+/// Storage of a word in `claimed_bitmap`: 40 bytes of record overhead,
+/// the key of storage prefix, campaign id and word index, and the word.
+const BITMAP_WORD_STORAGE: u64 = 40 + 1 + 4 + 4 + 16;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Campaign {
+    pub root: Vec<u8>,
+    /// number of entries, whose indexes are from 0 to `entry_count` - 1
+    pub entry_count: u32,
+    pub total_amount: Balance,
+    pub claimed_amount: Balance,
+    /// after this time, owner can reclaim the unclaimed amount
+    pub expire_time_in_sec: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CampaignInfo {
+    pub campaign_id: u32,
+    pub root: Base64VecU8,
+    pub entry_count: u32,
+    pub total_amount: U128,
+    pub claimed_amount: U128,
+    pub expire_time_in_sec: u32,
+}
+
+impl From<(u32, Campaign)> for CampaignInfo {
+    fn from((campaign_id, campaign): (u32, Campaign)) -> Self {
+        CampaignInfo {
+            campaign_id,
+            root: campaign.root.into(),
+            entry_count: campaign.entry_count,
+            total_amount: campaign.total_amount.into(),
+            claimed_amount: campaign.claimed_amount.into(),
+            expire_time_in_sec: campaign.expire_time_in_sec,
+        }
+    }
+}
+
+fn leaf_hash(index: u32, account_id: &AccountId, amount: Balance) -> Vec<u8> {
+    let mut data = Vec::with_capacity(1 + 4 + 16 + account_id.len());
+    data.push(LEAF_PREFIX);
+    data.extend_from_slice(&index.to_le_bytes());
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(account_id.as_bytes());
+    env::sha256(&data)
+}
+
+fn verify_proof(root: &[u8], leaf: Vec<u8>, proof: Vec<Base64VecU8>) -> bool {
+    let mut hash = leaf;
+    for sibling in proof {
+        let sibling: Vec<u8> = sibling.into();
+        assert_eq!(sibling.len(), HASH_LEN, "ERR_INVALID_PROOF_HASH");
+        let mut pair = Vec::with_capacity(1 + 2 * HASH_LEN);
+        pair.push(NODE_PREFIX);
+        if hash <= sibling {
+            pair.extend_from_slice(&hash);
+            pair.extend_from_slice(&sibling);
+        } else {
+            pair.extend_from_slice(&sibling);
+            pair.extend_from_slice(&hash);
+        }
+        hash = env::sha256(&pair);
+    }
+    hash == root
+}
+
+fn cur_time_in_sec() -> u32 {
+    (env::block_timestamp() / 1_000_000_000) as u32
+}
+
+fn bitmap_words(entry_count: u32) -> u32 {
+    (entry_count + BITMAP_WORD_BITS - 1) / BITMAP_WORD_BITS
+}
+
+/// NEAR to pay for the claimed bitmap of `entry_count` entries.
+fn bitmap_storage_cost(entry_count: u32) -> Balance {
+    bitmap_words(entry_count) as Balance * BITMAP_WORD_STORAGE as Balance * env::storage_byte_cost()
+}
+
+impl Contract {
+    fn internal_is_claimed(&self, campaign_id: u32, index: u32) -> bool {
+        let word = self
+            .claimed_bitmap
+            .get(&(campaign_id, index / BITMAP_WORD_BITS))
+            .unwrap_or(0);
+        word & (1u128 << (index % BITMAP_WORD_BITS)) != 0
+    }
+
+    fn internal_set_claimed(&mut self, campaign_id: u32, index: u32) {
+        let key = (campaign_id, index / BITMAP_WORD_BITS);
+        let word = self.claimed_bitmap.get(&key).unwrap_or(0);
+        self.claimed_bitmap
+            .insert(&key, &(word | (1u128 << (index % BITMAP_WORD_BITS))));
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Register a campaign of `total_amount` for the `entry_count` entries under merkle `root`,
+    /// escrowed from owner's balance until claimed or reclaimed after `expire_time_in_sec`.
+    /// Requires attached NEAR for the storage of the claimed bitmap, the rest is refunded.
+    /// Return the campaign id. Only can be called by owner.
+    #[payable]
+    pub fn add_campaign(
+        &mut self,
+        root: Base64VecU8,
+        entry_count: u32,
+        total_amount: U128,
+        expire_time_in_sec: u32,
+    ) -> u32 {
+        self.assert_owner();
+        let root: Vec<u8> = root.into();
+        assert_eq!(root.len(), HASH_LEN, "ERR_INVALID_ROOT");
+        assert!(entry_count > 0, "ERR_CAMPAIGN_NO_ENTRY");
+        let storage_cost = bitmap_storage_cost(entry_count);
+        assert!(env::attached_deposit() >= storage_cost, "ERR_INSUFFICIENT_STORAGE_DEPOSIT");
+        let refund = env::attached_deposit() - storage_cost;
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        let total_amount: Balance = total_amount.into();
+        assert!(total_amount > 0, "ERR_CAMPAIGN_ZERO_AMOUNT");
+        assert!(expire_time_in_sec > cur_time_in_sec(), "ERR_INVALID_EXPIRE_TIME");
+
+        let contract_id = env::current_account_id();
+        if !self.ft.accounts.contains_key(&contract_id) {
+            self.ft.internal_register_account(&contract_id);
+        }
+        let owner_id = self.owner_id.clone();
+        self.internal_transfer(&owner_id, &contract_id, total_amount, Some("airdrop escrow".to_string()));
+        self.airdrop_escrow += total_amount;
+
+        let campaign_id = self.next_campaign_id;
+        self.next_campaign_id += 1;
+        self.campaigns.insert(
+            &campaign_id,
+            &Campaign {
+                root,
+                entry_count,
+                total_amount,
+                claimed_amount: 0,
+                expire_time_in_sec,
+            },
+        );
+        campaign_id
+    }
+
+    /// Claim the `amount` at entry `index` of a campaign for the predecessor, with the merkle `proof` of the entry.
+    /// Requirements:
+    /// * The campaign is not reclaimed yet.
+    /// * The entry is not claimed yet.
+    /// * The predecessor should be registered.
+    pub fn claim(&mut self, campaign_id: u32, index: u32, amount: U128, proof: Vec<Base64VecU8>) {
+        let account_id = env::predecessor_account_id();
+        let amount: Balance = amount.into();
+        let mut campaign = self.campaigns.get(&campaign_id).expect("ERR_CAMPAIGN_NOT_FOUND");
+        assert!(index < campaign.entry_count, "ERR_INVALID_INDEX");
+        assert!(!self.internal_is_claimed(campaign_id, index), "ERR_ALREADY_CLAIMED");
+        assert!(
+            verify_proof(&campaign.root, leaf_hash(index, &account_id, amount), proof),
+            "ERR_INVALID_PROOF"
+        );
+        assert!(
+            campaign.claimed_amount + amount <= campaign.total_amount,
+            "ERR_CAMPAIGN_AMOUNT_EXCEEDED"
+        );
+
+        self.internal_set_claimed(campaign_id, index);
+        campaign.claimed_amount += amount;
+        self.campaigns.insert(&campaign_id, &campaign);
+        self.airdrop_escrow -= amount;
+        self.internal_transfer(
+            &env::current_account_id(),
+            &account_id,
+            amount,
+            Some(format!("airdrop {}", campaign_id)),
+        );
+    }
+
+    /// Return the unclaimed amount of an expired campaign to owner, and close the campaign.
+    /// The claimed bitmap is cleared and its storage is refunded to owner.
+    /// Only can be called by owner.
+    pub fn reclaim_campaign(&mut self, campaign_id: u32) -> U128 {
+        self.assert_owner();
+        let campaign = self.campaigns.get(&campaign_id).expect("ERR_CAMPAIGN_NOT_FOUND");
+        assert!(cur_time_in_sec() > campaign.expire_time_in_sec, "ERR_CAMPAIGN_NOT_EXPIRED");
+        self.campaigns.remove(&campaign_id);
+        for word in 0..bitmap_words(campaign.entry_count) {
+            self.claimed_bitmap.remove(&(campaign_id, word));
+        }
+        Promise::new(self.owner_id.clone()).transfer(bitmap_storage_cost(campaign.entry_count));
+        let unclaimed = campaign.total_amount - campaign.claimed_amount;
+        self.airdrop_escrow -= unclaimed;
+        if unclaimed > 0 {
+            let owner_id = self.owner_id.clone();
+            self.internal_transfer(
+                &env::current_account_id(),
+                &owner_id,
+                unclaimed,
+                Some(format!("reclaim airdrop {}", campaign_id)),
+            );
+        }
+        unclaimed.into()
+    }
+
+    /// Return all open campaigns.
+    pub fn get_campaigns(&self) -> Vec<CampaignInfo> {
+        self.campaigns.iter().map(|item| item.into()).collect()
+    }
+
+    pub fn get_campaign(&self, campaign_id: u32) -> Option<CampaignInfo> {
+        self.campaigns
+            .get(&campaign_id)
+            .map(|campaign| (campaign_id, campaign).into())
+    }
+
+    /// Return whether entry `index` of a campaign is claimed.
+    pub fn is_claimed(&self, campaign_id: u32, index: u32) -> bool {
+        self.internal_is_claimed(campaign_id, index)
+    }
+}
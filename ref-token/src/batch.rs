@@ -0,0 +1,48 @@
+//! Transfer to many receivers in one call, for payroll and grant payouts.
+
+use crate::*;
+use crate::events::emit_ft_transfer;
+use near_sdk::assert_one_yocto;
+
+#[near_bindgen]
+impl Contract {
+    /// Transfer each amount in `receivers` from the predecessor, all or nothing.
+    /// Requires attached deposit of exactly 1 yoctoNEAR.
+    /// Requirements:
+    /// * Every receiver should be registered, and not the predecessor.
+    /// * Every amount should be positive.
+    /// * The predecessor should hold the sum of amounts.
+    #[payable]
+    pub fn ft_batch_transfer(&mut self, receivers: Vec<(ValidAccountId, U128)>, memo: Option<String>) {
+        assert_one_yocto();
+        assert!(!receivers.is_empty(), "ERR_NO_RECEIVERS");
+        let sender_id = env::predecessor_account_id();
+        self.assert_not_frozen(&sender_id);
+        let receivers: Vec<(AccountId, Balance)> = receivers
+            .into_iter()
+            .map(|(receiver_id, amount)| (receiver_id.into(), amount.into()))
+            .collect();
+
+        let mut total_amount: Balance = 0;
+        for (receiver_id, amount) in receivers.iter() {
+            assert_ne!(&sender_id, receiver_id, "Sender and receiver should be different");
+            assert!(*amount > 0, "The amount should be a positive number");
+            assert!(
+                self.ft.accounts.contains_key(receiver_id),
+                "The account {} is not registered",
+                receiver_id
+            );
+            total_amount = total_amount.checked_add(*amount).expect("ERR_TOTAL_AMOUNT_OVERFLOW");
+        }
+
+        self.internal_checkpoint_accounts(&[&sender_id]);
+        for (receiver_id, _) in receivers.iter() {
+            self.internal_checkpoint_accounts(&[receiver_id]);
+        }
+        self.ft.internal_withdraw(&sender_id, total_amount);
+        for (receiver_id, amount) in receivers.iter() {
+            self.ft.internal_deposit(receiver_id, *amount);
+        }
+        emit_ft_transfer(&sender_id, &receivers, memo.as_deref());
+    }
+}
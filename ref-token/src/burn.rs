@@ -0,0 +1,48 @@
+//! Burning of REF, with the cumulative burned supply tracked for audits.
+
+use crate::*;
+use crate::events::emit_ft_burn;
+use near_sdk::assert_one_yocto;
+
+impl Contract {
+    /// Tokens burned when the sender of `ft_transfer_call` has unregistered before the refund.
+    pub(crate) fn on_tokens_burned(&mut self, account_id: AccountId, amount: Balance) {
+        self.burned_supply += amount;
+        log!("Account @{} burned {}", account_id, amount);
+    }
+
+    /// Tokens burned when an account with positive balance is force unregistered.
+    pub(crate) fn on_account_closed(&mut self, account_id: AccountId, balance: Balance) {
+        // the account is already removed, so checkpoint with the values before.
+        self.internal_checkpoint_balance(&account_id, balance);
+        self.internal_checkpoint_supply(self.ft.total_supply + balance);
+        if balance > 0 {
+            emit_ft_burn(&account_id, balance, Some("account closed"));
+        }
+        self.burned_supply += balance;
+        log!("Closed @{} with {}", account_id, balance);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Burn `amount` of the predecessor's token.
+    /// Requires attached deposit of exactly 1 yoctoNEAR.
+    #[payable]
+    pub fn ft_burn(&mut self, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let amount: Balance = amount.into();
+        assert!(amount > 0, "The amount should be a positive number");
+        self.internal_checkpoint_accounts(&[&account_id]);
+        self.internal_checkpoint_total_supply();
+        self.ft.internal_withdraw(&account_id, amount);
+        self.burned_supply += amount;
+        emit_ft_burn(&account_id, amount, memo.as_deref());
+    }
+
+    /// Return the cumulative burned supply.
+    pub fn get_burned_supply(&self) -> U128 {
+        self.burned_supply.into()
+    }
+}
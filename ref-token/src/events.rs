@@ -0,0 +1,82 @@
+//! NEP-297 events of NEP-141, and of this contract's own controls, for indexers to pick up from the logs.
+
+use near_sdk::serde_json::{json, Map, Value};
+use near_sdk::{log, AccountId, Balance};
+
+const NEP141_STANDARD: &str = "nep141";
+const NEP141_VERSION: &str = "1.0.0";
+const REF_TOKEN_STANDARD: &str = "ref-token";
+const REF_TOKEN_VERSION: &str = "1.0.0";
+
+fn log_event(standard: &str, version: &str, event: &str, data: Vec<Value>) {
+    log!(
+        "EVENT_JSON:{}",
+        json!({
+            "standard": standard,
+            "version": version,
+            "event": event,
+            "data": data,
+        })
+    );
+}
+
+fn emit_event(event: &str, data: Vec<Value>) {
+    log_event(NEP141_STANDARD, NEP141_VERSION, event, data);
+}
+
+/// Event of standard `ref-token`, for controls outside NEP-141.
+pub(crate) fn emit_ref_token_event(event: &str, data: Value) {
+    log_event(REF_TOKEN_STANDARD, REF_TOKEN_VERSION, event, vec![data]);
+}
+
+/// Data entry with `memo` left out when there is none.
+fn event_data(fields: Vec<(&str, Value)>, memo: Option<&str>) -> Value {
+    let mut data = Map::new();
+    for (key, value) in fields {
+        data.insert(key.to_string(), value);
+    }
+    if let Some(memo) = memo {
+        data.insert("memo".to_string(), json!(memo));
+    }
+    Value::Object(data)
+}
+
+/// One `ft_transfer` event from `old_owner_id`, with a data entry for each receiver.
+pub(crate) fn emit_ft_transfer(old_owner_id: &AccountId, receivers: &[(AccountId, Balance)], memo: Option<&str>) {
+    emit_event(
+        "ft_transfer",
+        receivers
+            .iter()
+            .map(|(new_owner_id, amount)| {
+                event_data(
+                    vec![
+                        ("old_owner_id", json!(old_owner_id)),
+                        ("new_owner_id", json!(new_owner_id)),
+                        ("amount", json!(amount.to_string())),
+                    ],
+                    memo,
+                )
+            })
+            .collect(),
+    );
+}
+
+pub(crate) fn emit_ft_mint(owner_id: &AccountId, amount: Balance, memo: Option<&str>) {
+    emit_event(
+        "ft_mint",
+        vec![event_data(
+            vec![("owner_id", json!(owner_id)), ("amount", json!(amount.to_string()))],
+            memo,
+        )],
+    );
+}
+
+pub(crate) fn emit_ft_burn(owner_id: &AccountId, amount: Balance, memo: Option<&str>) {
+    emit_event(
+        "ft_burn",
+        vec![event_data(
+            vec![("owner_id", json!(owner_id)), ("amount", json!(amount.to_string()))],
+            memo,
+        )],
+    );
+}
@@ -0,0 +1,153 @@
+//! Freezing of accounts, such as to hold stolen funds after an exploit.
+//!
+//! Frozen accounts can not send token. Owner freezes an account at once, so that funds are held
+//! before they can be moved, and the freeze expires by itself after `FREEZE_DURATION_IN_SEC`.
+//! Keeping an account frozen longer, or freezing it again within `FREEZE_DURATION_IN_SEC` after
+//! its freeze ended, takes a renewal that owner can only execute `FREEZE_RENEWAL_TIMELOCK_IN_SEC`
+//! after scheduling it. Every step logs an event, so that no account is frozen silently.
+
+use crate::*;
+use crate::events::emit_ref_token_event;
+use near_sdk::serde_json::json;
+
+/// Time a freeze lasts unless renewed.
+pub const FREEZE_DURATION_IN_SEC: u32 = 7 * 24 * 3600;
+
+/// Delay between scheduling a freeze renewal and executing it, shorter than a freeze
+/// so that a renewal scheduled at once can be executed before the freeze expires.
+pub const FREEZE_RENEWAL_TIMELOCK_IN_SEC: u32 = 2 * 24 * 3600;
+
+fn cur_time_in_sec() -> u32 {
+    (env::block_timestamp() / 1_000_000_000) as u32
+}
+
+impl Contract {
+    fn internal_is_frozen(&self, account_id: &AccountId) -> bool {
+        self.frozen_accounts
+            .get(account_id)
+            .map_or(false, |expire_in_sec| cur_time_in_sec() < expire_in_sec)
+    }
+
+    pub(crate) fn assert_not_frozen(&self, account_id: &AccountId) {
+        assert!(!self.internal_is_frozen(account_id), "ERR_ACCOUNT_FROZEN");
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Freeze `account_id` from now for `FREEZE_DURATION_IN_SEC`, unless it is frozen
+    /// or its freeze ended within `FREEZE_DURATION_IN_SEC`, which takes a renewal.
+    /// Return the time the freeze expires at. Only can be called by owner.
+    pub fn freeze(&mut self, account_id: ValidAccountId) -> u32 {
+        self.assert_owner();
+        let now = cur_time_in_sec();
+        if let Some(end_in_sec) = self.frozen_accounts.get(account_id.as_ref()) {
+            assert!(
+                now >= end_in_sec.saturating_add(FREEZE_DURATION_IN_SEC),
+                "ERR_FREEZE_RENEWAL_REQUIRED"
+            );
+        }
+        // a renewal left from the previous freeze doesn't carry over
+        self.pending_freeze_renewals.remove(account_id.as_ref());
+        let expire_in_sec = now + FREEZE_DURATION_IN_SEC;
+        self.frozen_accounts.insert(account_id.as_ref(), &expire_in_sec);
+        emit_ref_token_event(
+            "account_frozen",
+            json!({
+                "account_id": account_id,
+                "expire_in_sec": expire_in_sec,
+            }),
+        );
+        expire_in_sec
+    }
+
+    /// Schedule to renew the freeze of `account_id`, which has been frozen before,
+    /// for `FREEZE_DURATION_IN_SEC` more after `FREEZE_RENEWAL_TIMELOCK_IN_SEC`.
+    /// Return the time it can be executed from. Only can be called by owner.
+    pub fn schedule_freeze_renewal(&mut self, account_id: ValidAccountId) -> u32 {
+        self.assert_owner();
+        assert!(self.frozen_accounts.get(account_id.as_ref()).is_some(), "ERR_ACCOUNT_NOT_FROZEN");
+        assert!(
+            self.pending_freeze_renewals.get(account_id.as_ref()).is_none(),
+            "ERR_FREEZE_RENEWAL_PENDING"
+        );
+        let eta_in_sec = cur_time_in_sec() + FREEZE_RENEWAL_TIMELOCK_IN_SEC;
+        self.pending_freeze_renewals.insert(account_id.as_ref(), &eta_in_sec);
+        emit_ref_token_event(
+            "freeze_renewal_scheduled",
+            json!({
+                "account_id": account_id,
+                "eta_in_sec": eta_in_sec,
+            }),
+        );
+        eta_in_sec
+    }
+
+    /// Cancel the pending freeze renewal of `account_id`.
+    /// Only can be called by owner.
+    pub fn cancel_freeze_renewal(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        self.pending_freeze_renewals
+            .remove(account_id.as_ref())
+            .expect("ERR_NO_PENDING_FREEZE_RENEWAL");
+        emit_ref_token_event("freeze_renewal_cancelled", json!({ "account_id": account_id }));
+    }
+
+    /// Execute the pending freeze renewal of `account_id` after its timelock, extending the freeze
+    /// by `FREEZE_DURATION_IN_SEC`, or freezing from now if it has ended.
+    /// Return the time the freeze expires at. Only can be called by owner.
+    pub fn execute_freeze_renewal(&mut self, account_id: ValidAccountId) -> u32 {
+        self.assert_owner();
+        let eta_in_sec = self
+            .pending_freeze_renewals
+            .get(account_id.as_ref())
+            .expect("ERR_NO_PENDING_FREEZE_RENEWAL");
+        let now = cur_time_in_sec();
+        assert!(now >= eta_in_sec, "ERR_FREEZE_RENEWAL_TIMELOCKED");
+        self.pending_freeze_renewals.remove(account_id.as_ref());
+        let end_in_sec = self.frozen_accounts.get(account_id.as_ref()).unwrap();
+        let expire_in_sec = std::cmp::max(end_in_sec, now) + FREEZE_DURATION_IN_SEC;
+        self.frozen_accounts.insert(account_id.as_ref(), &expire_in_sec);
+        emit_ref_token_event(
+            "freeze_renewed",
+            json!({
+                "account_id": account_id,
+                "expire_in_sec": expire_in_sec,
+            }),
+        );
+        expire_in_sec
+    }
+
+    /// Lift the freeze of `account_id` now, dropping its pending renewal if any.
+    /// Only can be called by owner.
+    pub fn unfreeze(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        assert!(self.internal_is_frozen(account_id.as_ref()), "ERR_ACCOUNT_NOT_FROZEN");
+        self.frozen_accounts.insert(account_id.as_ref(), &cur_time_in_sec());
+        self.pending_freeze_renewals.remove(account_id.as_ref());
+        emit_ref_token_event("account_unfrozen", json!({ "account_id": account_id }));
+    }
+
+    pub fn is_frozen(&self, account_id: ValidAccountId) -> bool {
+        self.internal_is_frozen(account_id.as_ref())
+    }
+
+    /// Return accounts ever frozen with the time their freeze expires or was lifted at in pages,
+    /// those not frozen anymore are kept for the renewal rule.
+    pub fn get_frozen_accounts(&self, from_index: u64, limit: u64) -> Vec<(AccountId, u32)> {
+        let keys = self.frozen_accounts.keys_as_vector();
+        let values = self.frozen_accounts.values_as_vector();
+        (from_index..std::cmp::min(from_index.saturating_add(limit), keys.len()))
+            .map(|index| (keys.get(index).unwrap(), values.get(index).unwrap()))
+            .collect()
+    }
+
+    /// Return pending freeze renewals with the time they can be executed from in pages.
+    pub fn get_pending_freeze_renewals(&self, from_index: u64, limit: u64) -> Vec<(AccountId, u32)> {
+        let keys = self.pending_freeze_renewals.keys_as_vector();
+        let values = self.pending_freeze_renewals.values_as_vector();
+        (from_index..std::cmp::min(from_index.saturating_add(limit), keys.len()))
+            .map(|index| (keys.get(index).unwrap(), values.get(index).unwrap()))
+            .collect()
+    }
+}
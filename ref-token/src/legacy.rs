@@ -0,0 +1,9 @@
+//! State layouts of previous releases, kept for `migrate`.
+
+use crate::*;
+
+/// Contract state as deployed in version 1.0.0.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV100 {
+    pub ft: FungibleToken,
+}
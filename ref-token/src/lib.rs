@@ -3,75 +3,297 @@
 *
 */
 use near_contract_standards::fungible_token::metadata::{
-    FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
+    FungibleTokenMetadata, FungibleTokenMetadataProvider,
 };
+use near_contract_standards::fungible_token::core::FungibleTokenCore;
+use near_contract_standards::fungible_token::core_impl::ext_fungible_token_receiver;
+use near_contract_standards::fungible_token::resolver::FungibleTokenResolver;
 use near_contract_standards::fungible_token::FungibleToken;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet, Vector};
 use near_sdk::json_types::{ValidAccountId, U128};
-// Needed by `impl_fungible_token_core` for old Rust.
-#[allow(unused_imports)]
-use near_sdk::env;
-use near_sdk::{near_bindgen, log, AccountId, Balance, PanicOnDefault, PromiseOrValue};
+use near_sdk::{
+    assert_one_yocto, env, ext_contract, near_bindgen, log, AccountId, Balance, BorshStorageKey, Gas, PanicOnDefault,
+    Promise, PromiseOrValue, PromiseResult, StorageUsage, Timestamp,
+};
+use crate::events::{emit_ft_burn, emit_ft_mint, emit_ft_transfer};
+use crate::metadata::default_metadata;
+use crate::stream::Stream;
+pub use crate::airdrop::CampaignInfo;
+pub use crate::stream::StreamInfo;
+use crate::airdrop::Campaign;
+use crate::snapshot::Checkpoint;
 
+mod owner;
+mod legacy;
+mod metadata;
+mod events;
+mod mint;
+mod burn;
+mod stream;
+mod airdrop;
+mod batch;
+mod snapshot;
+mod freeze;
 
 near_sdk::setup_alloc!();
 
+const NO_DEPOSIT: Balance = 0;
+const GAS_FOR_RESOLVE_TRANSFER: Gas = 10_000_000_000_000;
+const GAS_FOR_FT_TRANSFER_CALL: Gas = 25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER;
+
+#[ext_contract(ext_self)]
+trait RefToken {
+    fn ft_resolve_transfer(&mut self, sender_id: AccountId, receiver_id: AccountId, amount: U128) -> U128;
+    fn on_stream_paid(&mut self, stream_id: u32, amount: U128) -> U128;
+}
+
+#[derive(BorshStorageKey, BorshSerialize)]
+pub(crate) enum StorageKey {
+    Metadata,
+    Minters,
+    Streams,
+    Campaigns,
+    ClaimedBitmap,
+    Snapshots,
+    Snapshotters,
+    AccountCheckpoints,
+    BalanceCheckpoints { account_hash: Vec<u8> },
+    SupplyCheckpoints,
+    FrozenAccounts,
+    PendingFreezeRenewals,
+}
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Contract {
     pub ft: FungibleToken,
+    pub owner_id: AccountId,
+    /// the account owner proposed to take over ownership, until it accepts
+    pub pending_owner_id: Option<AccountId>,
+    pub metadata: LazyOption<FungibleTokenMetadata>,
+    /// minting is off until owner sets it
+    pub max_supply: Option<Balance>,
+    /// remaining allowance of each minter
+    pub minters: UnorderedMap<AccountId, Balance>,
+    /// cumulative burned supply
+    pub burned_supply: Balance,
+    /// emission streams paid from the token held by this contract
+    pub streams: UnorderedMap<u32, Stream>,
+    pub next_stream_id: u32,
+    /// open airdrop campaigns
+    pub campaigns: UnorderedMap<u32, Campaign>,
+    pub next_campaign_id: u32,
+    /// claimed entries of each campaign, 128 entries a word, keyed by (campaign id, word index)
+    pub claimed_bitmap: LookupMap<(u32, u32), u128>,
+    /// unclaimed token of open campaigns, held by the contract account
+    pub airdrop_escrow: Balance,
+    /// timestamp of the snapshots in the window, the one of id `i` at index `(i - 1) % SNAPSHOT_WINDOW`
+    pub snapshots: Vector<Timestamp>,
+    /// id of the latest snapshot, 0 if none
+    pub last_snapshot_id: u32,
+    /// accounts besides owner allowed to take snapshots
+    pub snapshotters: UnorderedSet<AccountId>,
+    /// lazy checkpoints of balances at snapshots
+    pub balance_checkpoints: LookupMap<AccountId, Vector<Checkpoint>>,
+    /// lazy checkpoints of the total supply at snapshots
+    pub supply_checkpoints: Vector<Checkpoint>,
+    /// bytes the checkpoints take
+    pub checkpoint_storage_usage: StorageUsage,
+    /// bytes of checkpoints the snapshotters have paid for
+    pub checkpoint_storage_paid: StorageUsage,
+    /// accounts frozen, with the time their freeze expires or was lifted at
+    pub frozen_accounts: UnorderedMap<AccountId, u32>,
+    /// freeze renewals owner scheduled, with the time they can be executed from
+    pub pending_freeze_renewals: UnorderedMap<AccountId, u32>,
 }
 
 #[near_bindgen]
 impl Contract {
+    /// Mint `total_supply` to `owner`, which also owns the contract.
     #[init]
     pub fn new(owner: ValidAccountId, total_supply: U128) -> Self {
         let mut contract = Contract {
             ft: FungibleToken::new(b"a".to_vec()),
+            owner_id: owner.as_ref().clone(),
+            pending_owner_id: None,
+            metadata: LazyOption::new(StorageKey::Metadata, Some(&default_metadata())),
+            max_supply: None,
+            minters: UnorderedMap::new(StorageKey::Minters),
+            burned_supply: 0,
+            streams: UnorderedMap::new(StorageKey::Streams),
+            next_stream_id: 0,
+            campaigns: UnorderedMap::new(StorageKey::Campaigns),
+            next_campaign_id: 0,
+            claimed_bitmap: LookupMap::new(StorageKey::ClaimedBitmap),
+            airdrop_escrow: 0,
+            snapshots: Vector::new(StorageKey::Snapshots),
+            last_snapshot_id: 0,
+            snapshotters: UnorderedSet::new(StorageKey::Snapshotters),
+            balance_checkpoints: LookupMap::new(StorageKey::AccountCheckpoints),
+            supply_checkpoints: Vector::new(StorageKey::SupplyCheckpoints),
+            checkpoint_storage_usage: 0,
+            checkpoint_storage_paid: 0,
+            frozen_accounts: UnorderedMap::new(StorageKey::FrozenAccounts),
+            pending_freeze_renewals: UnorderedMap::new(StorageKey::PendingFreezeRenewals),
         };
         let amount: Balance = total_supply.into();
         contract.ft.internal_register_account(owner.as_ref());
         contract.ft.internal_deposit(owner.as_ref() , amount);
-        log!("Deposit {} token to {}", amount, owner);
+        emit_ft_mint(owner.as_ref(), amount, Some("new"));
         contract
     }
 }
 
-near_contract_standards::impl_fungible_token_core!(Contract, ft);
-near_contract_standards::impl_fungible_token_storage!(Contract, ft);
+impl Contract {
+    /// Transfer between registered accounts, logged as a NEP-297 `ft_transfer` event once done.
+    /// Unlike `FungibleToken::internal_transfer`, no legacy log is written besides the event.
+    pub(crate) fn internal_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) {
+        assert_ne!(sender_id, receiver_id, "Sender and receiver should be different");
+        assert!(amount > 0, "The amount should be a positive number");
+        self.internal_checkpoint_accounts(&[sender_id, receiver_id]);
+        self.ft.internal_withdraw(sender_id, amount);
+        self.ft.internal_deposit(receiver_id, amount);
+        emit_ft_transfer(sender_id, &[(receiver_id.clone(), amount)], memo.as_deref());
+    }
+
+    /// Call `ft_on_transfer` of the receiver for token already transferred, and resolve the refund,
+    /// as the second half of `ft_transfer_call`.
+    pub(crate) fn internal_transfer_call_promise(
+        &self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: Balance,
+        msg: String,
+        gas_for_ft_on_transfer: Gas,
+    ) -> Promise {
+        ext_fungible_token_receiver::ft_on_transfer(
+            sender_id.clone(),
+            amount.into(),
+            msg,
+            &receiver_id,
+            NO_DEPOSIT,
+            gas_for_ft_on_transfer,
+        )
+        .then(ext_self::ft_resolve_transfer(
+            sender_id,
+            receiver_id,
+            amount.into(),
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_TRANSFER,
+        ))
+    }
+
+    /// Refund the unused amount of `ft_transfer_call` from receiver as `FungibleToken::internal_ft_resolve_transfer` does,
+    /// without its legacy logs. Return the used amount and the amount burned as sender has unregistered.
+    fn internal_resolve_transfer(&mut self, sender_id: &AccountId, receiver_id: &AccountId, amount: Balance) -> (Balance, Balance) {
+        let unused_amount = match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(value) => {
+                if let Ok(unused_amount) = near_sdk::serde_json::from_slice::<U128>(&value) {
+                    std::cmp::min(amount, unused_amount.0)
+                } else {
+                    amount
+                }
+            }
+            PromiseResult::Failed => amount,
+        };
+        let receiver_balance = self.ft.accounts.get(receiver_id).unwrap_or(0);
+        let refund_amount = std::cmp::min(receiver_balance, unused_amount);
+        if refund_amount == 0 {
+            return (amount, 0);
+        }
+        self.ft.accounts.insert(receiver_id, &(receiver_balance - refund_amount));
+        if let Some(sender_balance) = self.ft.accounts.get(sender_id) {
+            self.ft.accounts.insert(sender_id, &(sender_balance + refund_amount));
+            (amount - refund_amount, 0)
+        } else {
+            self.ft.total_supply -= refund_amount;
+            (amount, refund_amount)
+        }
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenCore for Contract {
+    #[payable]
+    fn ft_transfer(&mut self, receiver_id: ValidAccountId, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.assert_not_frozen(&sender_id);
+        self.internal_transfer(&sender_id, receiver_id.as_ref(), amount.into(), memo);
+    }
+
+    #[payable]
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: ValidAccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        assert_one_yocto();
+        assert!(env::prepaid_gas() > GAS_FOR_FT_TRANSFER_CALL, "More gas is required");
+        let sender_id = env::predecessor_account_id();
+        self.assert_not_frozen(&sender_id);
+        let receiver_id: AccountId = receiver_id.into();
+        let amount: Balance = amount.into();
+        self.internal_transfer(&sender_id, &receiver_id, amount, memo);
+        self.internal_transfer_call_promise(
+            sender_id,
+            receiver_id,
+            amount,
+            msg,
+            env::prepaid_gas() - GAS_FOR_FT_TRANSFER_CALL,
+        )
+        .into()
+    }
+
+    fn ft_total_supply(&self) -> U128 {
+        self.ft.ft_total_supply()
+    }
+
+    fn ft_balance_of(&self, account_id: ValidAccountId) -> U128 {
+        self.ft.ft_balance_of(account_id)
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenResolver for Contract {
+    #[private]
+    fn ft_resolve_transfer(
+        &mut self,
+        sender_id: ValidAccountId,
+        receiver_id: ValidAccountId,
+        amount: U128,
+    ) -> U128 {
+        let sender_id: AccountId = sender_id.into();
+        let receiver_account_id: AccountId = receiver_id.as_ref().clone();
+        self.internal_checkpoint_accounts(&[&sender_id, &receiver_account_id]);
+        self.internal_checkpoint_total_supply();
+        let (used_amount, burned_amount) = self.internal_resolve_transfer(&sender_id, &receiver_account_id, amount.into());
+        // the refund is burned from receiver if sender has unregistered, and counted as used.
+        if burned_amount > 0 {
+            emit_ft_burn(&receiver_account_id, burned_amount, Some("refund burned"));
+            self.on_tokens_burned(sender_id, burned_amount);
+        } else if used_amount < amount.0 {
+            emit_ft_transfer(&receiver_account_id, &[(sender_id, amount.0 - used_amount)], Some("refund"));
+        }
+        used_amount.into()
+    }
+}
+near_contract_standards::impl_fungible_token_storage!(Contract, ft, on_account_closed);
 
 #[near_bindgen]
 impl FungibleTokenMetadataProvider for Contract {
     fn ft_metadata(&self) -> FungibleTokenMetadata {
-        let data_url = "data:image/svg+xml;base64,\
-        PD94bWwgdmVyc2lvbj0iMS4wIiBlbmNvZGluZz0idXRmLTgiPz4KPCEtLSBHZW5l\
-        cmF0b3I6IEFkb2JlIElsbHVzdHJhdG9yIDIxLjAuMCwgU1ZHIEV4cG9ydCBQbHVn\
-        LUluIC4gU1ZHIFZlcnNpb246IDYuMDAgQnVpbGQgMCkgIC0tPgo8c3ZnIHZlcnNp\
-        b249IjEuMSIgaWQ9IkxheWVyXzEiIHhtbG5zPSJodHRwOi8vd3d3LnczLm9yZy8y\
-        MDAwL3N2ZyIgeG1sbnM6eGxpbms9Imh0dHA6Ly93d3cudzMub3JnLzE5OTkveGxp\
-        bmsiIHg9IjBweCIgeT0iMHB4IgoJIHZpZXdCb3g9IjAgMCAyODggMzI0IiBzdHls\
-        ZT0iZW5hYmxlLWJhY2tncm91bmQ6bmV3IDAgMCAyODggMzI0OyIgeG1sOnNwYWNl\
-        PSJwcmVzZXJ2ZSI+CjxzdHlsZSB0eXBlPSJ0ZXh0L2NzcyI+Cgkuc3Qwe2ZpbGw6\
-        IzAwQzA4Qjt9Cjwvc3R5bGU+CjxnPgoJPHBhdGggZD0iTTE3My40LDE5MS40VjI2\
-        OEgyNTBMMTczLjQsMTkxLjR6IE0xMDcuMiwxMjUuMmwzMCwzMGwzMC4zLTMwLjNW\
-        NjkuMmgtNjAuNFYxMjUuMnogTTEwNy4yLDE1Mi4zVjI2OGg2MC40VjE1MmwtMzAu\
-        MywzMC4zCgkJTDEwNy4yLDE1Mi4zeiBNMTc3LjEsNjkuMmgtMy43VjExOUwyMTIs\
-        ODAuNUMyMDEuOCw3My4yLDE4OS42LDY5LjIsMTc3LjEsNjkuMnogTTM4LDE3NS41\
-        VjI2OGg2My4zVjE0Ni40bC0xNy4xLTE3LjFMMzgsMTc1LjV6CgkJIE0zOCwxNDgu\
-        NWw0Ni4yLTQ2LjJsMTcuMSwxNy4xVjY5LjJIMzhWMTQ4LjV6IE0yMzYuOCwxMjgu\
-        OUwyMzYuOCwxMjguOWMwLTEyLjUtMy45LTI0LjctMTEuMi0zNC44bC01Mi4xLDUy\
-        djQyLjRoMy43CgkJQzIxMC4xLDE4OC41LDIzNi44LDE2MS44LDIzNi44LDEyOC45\
-        eiIvPgoJPHBvbHlnb24gY2xhc3M9InN0MCIgcG9pbnRzPSIyMTAuMiw1NiAyNTAs\
-        OTUuOCAyNTAsNTYgCSIvPgo8L2c+Cjwvc3ZnPgo=";
-
-        FungibleTokenMetadata {
-            spec: FT_METADATA_SPEC.to_string(),
-            name: String::from("Ref Finance Token"),
-            symbol: String::from("REF"),
-            icon: Some(String::from(data_url)),
-            reference: None,
-            reference_hash: None,
-            decimals: 18,
-        }
+        self.metadata.get().unwrap()
     }
 }
@@ -0,0 +1,65 @@
+//! Token metadata kept in state, so that owner can update it without a release.
+
+use crate::*;
+use near_contract_standards::fungible_token::metadata::FT_METADATA_SPEC;
+use near_sdk::json_types::Base64VecU8;
+
+/// Metadata the contract is initialized with.
+pub(crate) fn default_metadata() -> FungibleTokenMetadata {
+    let data_url = "data:image/svg+xml;base64,\
+    PD94bWwgdmVyc2lvbj0iMS4wIiBlbmNvZGluZz0idXRmLTgiPz4KPCEtLSBHZW5l\
+    cmF0b3I6IEFkb2JlIElsbHVzdHJhdG9yIDIxLjAuMCwgU1ZHIEV4cG9ydCBQbHVn\
+    LUluIC4gU1ZHIFZlcnNpb246IDYuMDAgQnVpbGQgMCkgIC0tPgo8c3ZnIHZlcnNp\
+    b249IjEuMSIgaWQ9IkxheWVyXzEiIHhtbG5zPSJodHRwOi8vd3d3LnczLm9yZy8y\
+    MDAwL3N2ZyIgeG1sbnM6eGxpbms9Imh0dHA6Ly93d3cudzMub3JnLzE5OTkveGxp\
+    bmsiIHg9IjBweCIgeT0iMHB4IgoJIHZpZXdCb3g9IjAgMCAyODggMzI0IiBzdHls\
+    ZT0iZW5hYmxlLWJhY2tncm91bmQ6bmV3IDAgMCAyODggMzI0OyIgeG1sOnNwYWNl\
+    PSJwcmVzZXJ2ZSI+CjxzdHlsZSB0eXBlPSJ0ZXh0L2NzcyI+Cgkuc3Qwe2ZpbGw6\
+    IzAwQzA4Qjt9Cjwvc3R5bGU+CjxnPgoJPHBhdGggZD0iTTE3My40LDE5MS40VjI2\
+    OEgyNTBMMTczLjQsMTkxLjR6IE0xMDcuMiwxMjUuMmwzMCwzMGwzMC4zLTMwLjNW\
+    NjkuMmgtNjAuNFYxMjUuMnogTTEwNy4yLDE1Mi4zVjI2OGg2MC40VjE1MmwtMzAu\
+    MywzMC4zCgkJTDEwNy4yLDE1Mi4zeiBNMTc3LjEsNjkuMmgtMy43VjExOUwyMTIs\
+    ODAuNUMyMDEuOCw3My4yLDE4OS42LDY5LjIsMTc3LjEsNjkuMnogTTM4LDE3NS41\
+    VjI2OGg2My4zVjE0Ni40bC0xNy4xLTE3LjFMMzgsMTc1LjV6CgkJIE0zOCwxNDgu\
+    NWw0Ni4yLTQ2LjJsMTcuMSwxNy4xVjY5LjJIMzhWMTQ4LjV6IE0yMzYuOCwxMjgu\
+    OUwyMzYuOCwxMjguOWMwLTEyLjUtMy45LTI0LjctMTEuMi0zNC44bC01Mi4xLDUy\
+    djQyLjRoMy43CgkJQzIxMC4xLDE4OC41LDIzNi44LDE2MS44LDIzNi44LDEyOC45\
+    eiIvPgoJPHBvbHlnb24gY2xhc3M9InN0MCIgcG9pbnRzPSIyMTAuMiw1NiAyNTAs\
+    OTUuOCAyNTAsNTYgCSIvPgo8L2c+Cjwvc3ZnPgo=";
+
+    FungibleTokenMetadata {
+        spec: FT_METADATA_SPEC.to_string(),
+        name: String::from("Ref Finance Token"),
+        symbol: String::from("REF"),
+        icon: Some(String::from(data_url)),
+        reference: None,
+        reference_hash: None,
+        decimals: 18,
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Set the icon, usually a data URL. Only can be called by owner.
+    pub fn set_icon(&mut self, icon: Option<String>) {
+        self.assert_owner();
+        let mut metadata = self.metadata.get().unwrap();
+        metadata.icon = icon;
+        self.metadata.set(&metadata);
+    }
+
+    /// Set the reference to off-chain metadata with its sha256 hash, or clear both.
+    /// Only can be called by owner.
+    pub fn set_reference(&mut self, reference: Option<String>, reference_hash: Option<Base64VecU8>) {
+        self.assert_owner();
+        assert_eq!(reference.is_some(), reference_hash.is_some(), "ERR_REFERENCE_WITHOUT_HASH");
+        if let Some(reference_hash) = &reference_hash {
+            assert_eq!(reference_hash.0.len(), 32, "ERR_INVALID_REFERENCE_HASH");
+        }
+        let mut metadata = self.metadata.get().unwrap();
+        metadata.reference = reference;
+        metadata.reference_hash = reference_hash;
+        metadata.assert_valid();
+        self.metadata.set(&metadata);
+    }
+}
@@ -0,0 +1,85 @@
+//! Capped minting by minters, each within the allowance given by owner.
+//!
+//! Minting is off until owner sets the maximum supply,
+//! which can only be lowered afterwards, and never below the issued supply.
+//! The issued supply counts the burned supply as well, so burned token can't be minted again.
+
+use crate::*;
+use crate::events::emit_ft_mint;
+
+impl Contract {
+    /// Supply ever issued, the current supply plus the burned supply.
+    fn internal_issued_supply(&self) -> Balance {
+        self.ft.total_supply + self.burned_supply
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Set the maximum supply. Once set, it can only be lowered.
+    /// Only can be called by owner.
+    pub fn set_max_supply(&mut self, max_supply: U128) {
+        self.assert_owner();
+        let max_supply: Balance = max_supply.into();
+        if let Some(prev_max_supply) = self.max_supply {
+            assert!(max_supply <= prev_max_supply, "ERR_MAX_SUPPLY_CAN_ONLY_DECREASE");
+        }
+        assert!(max_supply >= self.internal_issued_supply(), "ERR_MAX_SUPPLY_BELOW_SUPPLY");
+        self.max_supply = Some(max_supply);
+    }
+
+    /// Set how much `minter_id` can still mint, 0 removes the minter.
+    /// Only can be called by owner.
+    pub fn set_minter_allowance(&mut self, minter_id: ValidAccountId, allowance: U128) {
+        self.assert_owner();
+        let allowance: Balance = allowance.into();
+        if allowance == 0 {
+            self.minters.remove(minter_id.as_ref());
+        } else {
+            self.minters.insert(minter_id.as_ref(), &allowance);
+        }
+        log!("Set minter {} allowance to {}", minter_id.as_ref(), allowance);
+    }
+
+    /// Mint `amount` to `account_id` within the allowance of the predecessor.
+    /// Requirements:
+    /// * The predecessor should be a minter with at least `amount` of allowance.
+    /// * The issued supply after minting should not exceed the maximum supply.
+    /// * `account_id` should be registered.
+    pub fn mint(&mut self, account_id: ValidAccountId, amount: U128) {
+        let minter_id = env::predecessor_account_id();
+        let amount: Balance = amount.into();
+        assert!(amount > 0, "ERR_MINT_ZERO_AMOUNT");
+        let allowance = self.minters.get(&minter_id).expect("ERR_NOT_MINTER");
+        assert!(allowance >= amount, "ERR_MINTER_ALLOWANCE_EXCEEDED");
+        let max_supply = self.max_supply.expect("ERR_MAX_SUPPLY_NOT_SET");
+        assert!(self.internal_issued_supply() + amount <= max_supply, "ERR_EXCEED_MAX_SUPPLY");
+
+        if allowance == amount {
+            self.minters.remove(&minter_id);
+        } else {
+            self.minters.insert(&minter_id, &(allowance - amount));
+        }
+        self.internal_checkpoint_accounts(&[account_id.as_ref()]);
+        self.internal_checkpoint_total_supply();
+        self.ft.internal_deposit(account_id.as_ref(), amount);
+        emit_ft_mint(account_id.as_ref(), amount, Some(&format!("mint by {}", minter_id)));
+    }
+
+    pub fn get_max_supply(&self) -> Option<U128> {
+        self.max_supply.map(|v| v.into())
+    }
+
+    /// Return the remaining allowance of `minter_id`.
+    pub fn get_minter_allowance(&self, minter_id: ValidAccountId) -> U128 {
+        self.minters.get(minter_id.as_ref()).unwrap_or(0).into()
+    }
+
+    /// Return minters with their remaining allowance.
+    pub fn get_minters(&self) -> Vec<(AccountId, U128)> {
+        self.minters
+            .iter()
+            .map(|(minter_id, allowance)| (minter_id, allowance.into()))
+            .collect()
+    }
+}
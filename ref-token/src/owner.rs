@@ -0,0 +1,153 @@
+//! Implement all the relevant logic for owner of this contract.
+
+use crate::*;
+use crate::legacy::ContractV100;
+
+#[near_bindgen]
+impl Contract {
+    /// Propose `owner_id` as the new owner, who takes over after calling `accept_owner`.
+    /// Proposing again replaces the previous proposal. Only can be called by owner.
+    pub fn propose_owner(&mut self, owner_id: ValidAccountId) {
+        self.assert_owner();
+        self.pending_owner_id = Some(owner_id.into());
+    }
+
+    /// Withdraw the ownership proposal. Only can be called by owner.
+    pub fn cancel_owner_proposal(&mut self) {
+        self.assert_owner();
+        self.pending_owner_id = None;
+    }
+
+    /// Take over ownership. Only can be called by the proposed owner.
+    pub fn accept_owner(&mut self) {
+        let pending_owner_id = self.pending_owner_id.take().expect("ERR_NO_PENDING_OWNER");
+        assert_eq!(
+            env::predecessor_account_id(),
+            pending_owner_id,
+            "ERR_NOT_ALLOWED"
+        );
+        log!("Owner changed from {} to {}", self.owner_id, pending_owner_id);
+        self.owner_id = pending_owner_id;
+    }
+
+    /// Get the owner of this account.
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    /// Get the proposed owner, if any.
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner_id.clone()
+    }
+
+    /// Get the version of this contract.
+    pub fn get_version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    pub(crate) fn assert_owner(&self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "ERR_NOT_ALLOWED"
+        );
+    }
+
+    /// Migration function.
+    /// For next version upgrades, change this function.
+    /// Version 1.0.0 has no owner, so `owner_id` becomes the owner,
+    /// upgrades keeping the state layout keep the current owner.
+    #[init(ignore_state)]
+    #[private]
+    pub fn migrate(owner_id: ValidAccountId) -> Self {
+        let state = env::storage_read(b"STATE").expect("ERR_NOT_INITIALIZED");
+        if let Ok(contract) = Contract::try_from_slice(&state) {
+            // upgrade to the same state layout, nothing to migrate.
+            return contract;
+        }
+        let prev = ContractV100::try_from_slice(&state).expect("ERR_UNKNOWN_STATE");
+        Contract {
+            ft: prev.ft,
+            owner_id: owner_id.into(),
+            pending_owner_id: None,
+            metadata: LazyOption::new(StorageKey::Metadata, Some(&default_metadata())),
+            max_supply: None,
+            minters: UnorderedMap::new(StorageKey::Minters),
+            burned_supply: 0,
+            streams: UnorderedMap::new(StorageKey::Streams),
+            next_stream_id: 0,
+            campaigns: UnorderedMap::new(StorageKey::Campaigns),
+            next_campaign_id: 0,
+            claimed_bitmap: LookupMap::new(StorageKey::ClaimedBitmap),
+            airdrop_escrow: 0,
+            snapshots: Vector::new(StorageKey::Snapshots),
+            last_snapshot_id: 0,
+            snapshotters: UnorderedSet::new(StorageKey::Snapshotters),
+            balance_checkpoints: LookupMap::new(StorageKey::AccountCheckpoints),
+            supply_checkpoints: Vector::new(StorageKey::SupplyCheckpoints),
+            checkpoint_storage_usage: 0,
+            checkpoint_storage_paid: 0,
+            frozen_accounts: UnorderedMap::new(StorageKey::FrozenAccounts),
+            pending_freeze_renewals: UnorderedMap::new(StorageKey::PendingFreezeRenewals),
+        }
+    }
+}
+
+
+#[cfg(target_arch = "wasm32")]
+mod upgrade {
+    use near_sdk::env::BLOCKCHAIN_INTERFACE;
+    use near_sdk::Gas;
+
+    use super::*;
+
+    const BLOCKCHAIN_INTERFACE_NOT_SET_ERR: &str = "Blockchain interface not set.";
+
+    /// Gas for calling migration call.
+    pub const GAS_FOR_MIGRATE_CALL: Gas = 5_000_000_000_000;
+
+    /// Self upgrade and call migrate, optimizes gas by not loading into memory the code.
+    /// Takes as input non serialized set of bytes of the code.
+    #[no_mangle]
+    pub extern "C" fn upgrade() {
+        env::setup_panic_hook();
+        env::set_blockchain_interface(Box::new(near_blockchain::NearBlockchain {}));
+        let contract: Contract = env::state_read().expect("ERR_CONTRACT_IS_NOT_INITIALIZED");
+        contract.assert_owner();
+        let current_id = env::current_account_id().into_bytes();
+        let method_name = "migrate".as_bytes().to_vec();
+        let args = format!("{{\"owner_id\": \"{}\"}}", contract.owner_id).into_bytes();
+        unsafe {
+            BLOCKCHAIN_INTERFACE.with(|b| {
+                // Load input into register 0.
+                b.borrow()
+                    .as_ref()
+                    .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
+                    .input(0);
+                let promise_id = b
+                    .borrow()
+                    .as_ref()
+                    .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
+                    .promise_batch_create(current_id.len() as _, current_id.as_ptr() as _);
+                b.borrow()
+                    .as_ref()
+                    .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
+                    .promise_batch_action_deploy_contract(promise_id, u64::MAX as _, 0);
+                let attached_gas = env::prepaid_gas() - env::used_gas() - GAS_FOR_MIGRATE_CALL;
+                b.borrow()
+                    .as_ref()
+                    .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
+                    .promise_batch_action_function_call(
+                        promise_id,
+                        method_name.len() as _,
+                        method_name.as_ptr() as _,
+                        args.len() as _,
+                        args.as_ptr() as _,
+                        0 as _,
+                        attached_gas,
+                    );
+            });
+        }
+    }
+
+}
@@ -0,0 +1,223 @@
+//! Balance snapshots for governance.
+//!
+//! An authorized account takes a snapshot, which only records its time.
+//! Balances are checkpointed lazily: before the first change of a balance, or of the total supply,
+//! after a snapshot, its value is pushed as the checkpoint of that snapshot,
+//! which is also the value at every earlier snapshot since the previous checkpoint.
+//! Values without any later checkpoint are the current ones.
+//!
+//! Only the latest `SNAPSHOT_WINDOW` snapshots can be queried, checkpoints only of older ones are pruned
+//! on the next push, so an account and the total supply each keep at most `SNAPSHOT_WINDOW` checkpoints.
+//! The storage the checkpoints take since the previous snapshot is paid by whoever takes the next one.
+
+use crate::*;
+use near_sdk::collections::Vector;
+use near_sdk::{StorageUsage, Timestamp};
+use std::cmp::max;
+
+/// Each checkpoint of an account takes about 105 bytes, so an account takes at most about 1 KB.
+pub const SNAPSHOT_WINDOW: u32 = 10;
+
+/// Value of a balance at snapshot `snapshot_id` and every earlier snapshot since the previous checkpoint.
+pub type Checkpoint = (u32, Balance);
+
+/// Value at `snapshot_id` out of `checkpoints` ordered by snapshot id, if any checkpoint is at or after it.
+fn value_at(checkpoints: &Vector<Checkpoint>, snapshot_id: u32) -> Option<Balance> {
+    let (mut low, mut high) = (0, checkpoints.len());
+    while low < high {
+        let mid = (low + high) / 2;
+        if checkpoints.get(mid).unwrap().0 < snapshot_id {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    checkpoints.get(low).map(|(_, value)| value)
+}
+
+/// The oldest snapshot that can still be queried when `cur_snapshot_id` is the latest.
+fn first_live_snapshot_id(cur_snapshot_id: u32) -> u32 {
+    cur_snapshot_id.saturating_sub(SNAPSHOT_WINDOW) + 1
+}
+
+/// Push `value` as the checkpoint of `snapshot_id` unless there is already one, return whether pushed.
+/// The checkpoints only of snapshots out of the window are pruned before.
+fn push_checkpoint(checkpoints: &mut Vector<Checkpoint>, snapshot_id: u32, value: Balance) -> bool {
+    let last_snapshot_id = checkpoints.len().checked_sub(1).and_then(|i| checkpoints.get(i)).map(|(id, _)| id);
+    if last_snapshot_id.map_or(false, |id| id >= snapshot_id) {
+        return false;
+    }
+    let first_live_id = first_live_snapshot_id(snapshot_id);
+    let expired = (0..checkpoints.len())
+        .take_while(|i| checkpoints.get(*i).unwrap().0 < first_live_id)
+        .count() as u64;
+    if expired > 0 {
+        for i in expired..checkpoints.len() {
+            checkpoints.replace(i - expired, &checkpoints.get(i).unwrap());
+        }
+        for _ in 0..expired {
+            checkpoints.pop();
+        }
+    }
+    checkpoints.push(&(snapshot_id, value));
+    true
+}
+
+impl Contract {
+    fn cur_snapshot_id(&self) -> u32 {
+        self.last_snapshot_id
+    }
+
+    /// Account the storage checkpoints took, or freed, since `initial_storage_usage`.
+    fn internal_track_checkpoint_storage(&mut self, initial_storage_usage: StorageUsage) {
+        self.checkpoint_storage_usage =
+            (self.checkpoint_storage_usage + env::storage_usage()).saturating_sub(initial_storage_usage);
+    }
+
+    /// NEAR the next snapshot pays for the checkpoint storage not paid yet.
+    fn internal_unpaid_checkpoint_storage_cost(&self) -> Balance {
+        self.checkpoint_storage_usage.saturating_sub(self.checkpoint_storage_paid) as Balance
+            * env::storage_byte_cost()
+    }
+
+    /// Checkpoint `balance` as the balance of `account_id` before its first change after the latest snapshot.
+    pub(crate) fn internal_checkpoint_balance(&mut self, account_id: &AccountId, balance: Balance) {
+        let snapshot_id = self.cur_snapshot_id();
+        if snapshot_id == 0 {
+            return;
+        }
+        let initial_storage_usage = env::storage_usage();
+        let mut checkpoints = self.balance_checkpoints.get(account_id).unwrap_or_else(|| {
+            Vector::new(StorageKey::BalanceCheckpoints {
+                account_hash: env::sha256(account_id.as_bytes()),
+            })
+        });
+        if push_checkpoint(&mut checkpoints, snapshot_id, balance) {
+            self.balance_checkpoints.insert(account_id, &checkpoints);
+            self.internal_track_checkpoint_storage(initial_storage_usage);
+        }
+    }
+
+    /// Checkpoint `total_supply` as the total supply before its first change after the latest snapshot.
+    pub(crate) fn internal_checkpoint_supply(&mut self, total_supply: Balance) {
+        let snapshot_id = self.cur_snapshot_id();
+        if snapshot_id > 0 {
+            let initial_storage_usage = env::storage_usage();
+            if push_checkpoint(&mut self.supply_checkpoints, snapshot_id, total_supply) {
+                self.internal_track_checkpoint_storage(initial_storage_usage);
+            }
+        }
+    }
+
+    /// Checkpoint the current balances of `account_ids` before changing them.
+    pub(crate) fn internal_checkpoint_accounts(&mut self, account_ids: &[&AccountId]) {
+        if self.cur_snapshot_id() == 0 {
+            return;
+        }
+        for account_id in account_ids {
+            let balance = self.ft.accounts.get(account_id).unwrap_or(0);
+            self.internal_checkpoint_balance(account_id, balance);
+        }
+    }
+
+    /// Checkpoint the current total supply before changing it.
+    pub(crate) fn internal_checkpoint_total_supply(&mut self) {
+        self.internal_checkpoint_supply(self.ft.total_supply);
+    }
+
+    fn assert_snapshot_id(&self, snapshot_id: u32) {
+        assert!(
+            snapshot_id > 0 && snapshot_id <= self.cur_snapshot_id(),
+            "ERR_INVALID_SNAPSHOT_ID"
+        );
+        assert!(
+            snapshot_id >= first_live_snapshot_id(self.cur_snapshot_id()),
+            "ERR_SNAPSHOT_EXPIRED"
+        );
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Take a snapshot of all balances and the total supply, return its id, starting from 1.
+    /// Requires attached NEAR for the storage checkpoints took since the previous snapshot,
+    /// see `get_snapshot_storage_cost`, the rest is refunded.
+    /// Only can be called by owner or a snapshotter.
+    #[payable]
+    pub fn snapshot(&mut self) -> u32 {
+        let predecessor_id = env::predecessor_account_id();
+        assert!(
+            predecessor_id == self.owner_id || self.snapshotters.contains(&predecessor_id),
+            "ERR_NOT_ALLOWED"
+        );
+        let storage_cost = self.internal_unpaid_checkpoint_storage_cost();
+        assert!(env::attached_deposit() >= storage_cost, "ERR_INSUFFICIENT_STORAGE_DEPOSIT");
+        let refund = env::attached_deposit() - storage_cost;
+        if refund > 0 {
+            Promise::new(predecessor_id.clone()).transfer(refund);
+        }
+        // storage freed by pruning stays paid for the checkpoints to come
+        self.checkpoint_storage_paid = max(self.checkpoint_storage_paid, self.checkpoint_storage_usage);
+
+        let snapshot_id = self.cur_snapshot_id() + 1;
+        // timestamps are kept in a ring of the window
+        if self.snapshots.len() < SNAPSHOT_WINDOW as u64 {
+            self.snapshots.push(&env::block_timestamp());
+        } else {
+            self.snapshots.replace(((snapshot_id - 1) % SNAPSHOT_WINDOW) as u64, &env::block_timestamp());
+        }
+        self.last_snapshot_id = snapshot_id;
+        log!("Snapshot {} by {}", snapshot_id, predecessor_id);
+        snapshot_id
+    }
+
+    /// Return the NEAR the next snapshot should attach for the storage checkpoints took since the previous one.
+    pub fn get_snapshot_storage_cost(&self) -> U128 {
+        self.internal_unpaid_checkpoint_storage_cost().into()
+    }
+
+    /// Allow `account_id` to take snapshots. Only can be called by owner.
+    pub fn add_snapshotter(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        self.snapshotters.insert(account_id.as_ref());
+    }
+
+    /// Only can be called by owner.
+    pub fn remove_snapshotter(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        self.snapshotters.remove(account_id.as_ref());
+    }
+
+    pub fn get_snapshotters(&self) -> Vec<AccountId> {
+        self.snapshotters.to_vec()
+    }
+
+    /// Return the id of the latest snapshot, 0 if none.
+    pub fn get_current_snapshot_id(&self) -> u32 {
+        self.cur_snapshot_id()
+    }
+
+    /// Return the block timestamp in nanoseconds the snapshot was taken at.
+    pub fn get_snapshot_timestamp(&self, snapshot_id: u32) -> Timestamp {
+        self.assert_snapshot_id(snapshot_id);
+        self.snapshots.get(((snapshot_id - 1) % SNAPSHOT_WINDOW) as u64).unwrap()
+    }
+
+    /// Return the balance of `account_id` at the snapshot.
+    pub fn balance_of_at(&self, account_id: ValidAccountId, snapshot_id: u32) -> U128 {
+        self.assert_snapshot_id(snapshot_id);
+        self.balance_checkpoints
+            .get(account_id.as_ref())
+            .and_then(|checkpoints| value_at(&checkpoints, snapshot_id))
+            .unwrap_or_else(|| self.ft.accounts.get(account_id.as_ref()).unwrap_or(0))
+            .into()
+    }
+
+    /// Return the total supply at the snapshot.
+    pub fn total_supply_at(&self, snapshot_id: u32) -> U128 {
+        self.assert_snapshot_id(snapshot_id);
+        value_at(&self.supply_checkpoints, snapshot_id)
+            .unwrap_or(self.ft.total_supply)
+            .into()
+    }
+}
@@ -0,0 +1,224 @@
+//! Streaming emission out of the token held by this contract itself.
+//!
+//! Owner funds the emission by registering the contract account with `storage_deposit`,
+//! which a fresh deployment doesn't do, then transferring token to it, and configures streams, each accruing `amount_per_sec` to a receiver between its start and end time.
+//! Anyone can pay the accrued amount of a stream, which is sent with `ft_transfer_call`,
+//! so a staking pool like xref-token gets its reward without manual operations.
+//! If the contract holds less than the accrued amount, the rest stays accrued until funded,
+//! and what the receiver refunds of a payment is accrued again.
+
+use crate::*;
+use near_sdk::serde::{Deserialize, Serialize};
+use std::cmp::{max, min};
+
+const GAS_FOR_FT_ON_TRANSFER: Gas = 30_000_000_000_000;
+const GAS_FOR_ON_STREAM_PAID: Gas = 10_000_000_000_000;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Stream {
+    pub receiver_id: AccountId,
+    /// msg of `ft_transfer_call` to the receiver
+    pub msg: String,
+    pub amount_per_sec: Balance,
+    pub start_time_in_sec: u32,
+    pub end_time_in_sec: u32,
+    /// the stream has accrued until this time
+    pub accrued_time_in_sec: u32,
+    /// accrued but not paid yet
+    pub unpaid_amount: Balance,
+}
+
+impl Stream {
+    /// Accrued amount until `cur_time_in_sec`, including the unpaid part.
+    fn accrued_amount(&self, cur_time_in_sec: u32) -> Balance {
+        let cur_time_in_sec = min(cur_time_in_sec, self.end_time_in_sec);
+        if cur_time_in_sec > self.accrued_time_in_sec {
+            self.amount_per_sec
+                .checked_mul((cur_time_in_sec - self.accrued_time_in_sec) as Balance)
+                .and_then(|amount| amount.checked_add(self.unpaid_amount))
+                .expect("ERR_STREAM_OVERFLOW")
+        } else {
+            self.unpaid_amount
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StreamInfo {
+    pub stream_id: u32,
+    pub receiver_id: AccountId,
+    pub msg: String,
+    pub amount_per_sec: U128,
+    pub start_time_in_sec: u32,
+    pub end_time_in_sec: u32,
+    // at call time, the amount anyone can pay to the receiver
+    pub accrued_amount: U128,
+}
+
+fn cur_time_in_sec() -> u32 {
+    (env::block_timestamp() / 1_000_000_000) as u32
+}
+
+impl Contract {
+    /// Token held by this contract that is free for emission, not escrowed for airdrops.
+    pub(crate) fn internal_treasury_balance(&self) -> Balance {
+        self.ft
+            .accounts
+            .get(&env::current_account_id())
+            .unwrap_or(0)
+            .saturating_sub(self.airdrop_escrow)
+    }
+
+    fn stream_info(&self, stream_id: u32, stream: &Stream) -> StreamInfo {
+        StreamInfo {
+            stream_id,
+            receiver_id: stream.receiver_id.clone(),
+            msg: stream.msg.clone(),
+            amount_per_sec: stream.amount_per_sec.into(),
+            start_time_in_sec: stream.start_time_in_sec,
+            end_time_in_sec: stream.end_time_in_sec,
+            accrued_amount: stream.accrued_amount(cur_time_in_sec()).into(),
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Add a stream that accrues `amount_per_sec` to `receiver_id` from `start_time_in_sec` to `end_time_in_sec`,
+    /// paid with `ft_transfer_call` carrying `msg`, e.g. "reward" for xref-token. Return the stream id.
+    /// Only can be called by owner.
+    pub fn add_stream(
+        &mut self,
+        receiver_id: ValidAccountId,
+        msg: String,
+        amount_per_sec: U128,
+        start_time_in_sec: u32,
+        end_time_in_sec: u32,
+    ) -> u32 {
+        self.assert_owner();
+        assert_ne!(receiver_id.as_ref(), &env::current_account_id(), "ERR_INVALID_STREAM_RECEIVER");
+        let start_time_in_sec = max(start_time_in_sec, cur_time_in_sec());
+        assert!(start_time_in_sec < end_time_in_sec, "ERR_INVALID_STREAM_TIME");
+        // so the accrued amount never overflows
+        let amount_per_sec: Balance = amount_per_sec.into();
+        amount_per_sec
+            .checked_mul((end_time_in_sec - start_time_in_sec) as Balance)
+            .expect("ERR_STREAM_OVERFLOW");
+        let stream_id = self.next_stream_id;
+        self.next_stream_id += 1;
+        self.streams.insert(
+            &stream_id,
+            &Stream {
+                receiver_id: receiver_id.into(),
+                msg,
+                amount_per_sec,
+                start_time_in_sec,
+                end_time_in_sec,
+                accrued_time_in_sec: start_time_in_sec,
+                unpaid_amount: 0,
+            },
+        );
+        stream_id
+    }
+
+    /// Remove a stream after paying its accrued amount as `pay_stream` does,
+    /// the part the contract can't pay is dropped and logged, so is all of it if the receiver has unregistered.
+    /// Only can be called by owner, with at least 70 TGas attached.
+    pub fn remove_stream(&mut self, stream_id: u32) -> PromiseOrValue<U128> {
+        self.assert_owner();
+        let mut stream = self.streams.get(&stream_id).expect("ERR_STREAM_NOT_FOUND");
+        // a stream to this contract itself predates the receiver check in `add_stream` and can't be paid either
+        let result = if stream.receiver_id != env::current_account_id()
+            && self.ft.accounts.contains_key(&stream.receiver_id)
+        {
+            self.internal_pay_stream(stream_id, &mut stream)
+        } else {
+            stream.unpaid_amount = stream.accrued_amount(cur_time_in_sec());
+            PromiseOrValue::Value(U128(0))
+        };
+        self.streams.remove(&stream_id);
+        if stream.unpaid_amount > 0 {
+            log!("Stream {} removed with {} unpaid", stream_id, stream.unpaid_amount);
+        }
+        result
+    }
+
+    /// Pay the accrued amount of a stream, as much as the contract holds, to its receiver.
+    /// Anyone can call, with at least 70 TGas attached.
+    pub fn pay_stream(&mut self, stream_id: u32) -> PromiseOrValue<U128> {
+        let mut stream = self.streams.get(&stream_id).expect("ERR_STREAM_NOT_FOUND");
+        self.internal_pay_stream(stream_id, &mut stream)
+    }
+
+    /// Return all streams.
+    pub fn get_streams(&self) -> Vec<StreamInfo> {
+        self.streams
+            .iter()
+            .map(|(stream_id, stream)| self.stream_info(stream_id, &stream))
+            .collect()
+    }
+
+    /// Return the stream of given id.
+    pub fn get_stream(&self, stream_id: u32) -> Option<StreamInfo> {
+        self.streams.get(&stream_id).map(|stream| self.stream_info(stream_id, &stream))
+    }
+
+    /// Accrue again what the receiver refunded of a payment of `amount`, unless the stream has been removed.
+    /// Return the amount the receiver used.
+    #[private]
+    pub fn on_stream_paid(&mut self, stream_id: u32, amount: U128) -> U128 {
+        let amount: Balance = amount.into();
+        let used_amount = match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(value) => near_sdk::serde_json::from_slice::<U128>(&value)
+                .map(|used_amount| min(used_amount.0, amount))
+                .unwrap_or(amount),
+            // a failed resolve refunds nothing
+            PromiseResult::Failed => amount,
+        };
+        let refund = amount - used_amount;
+        if refund > 0 {
+            if let Some(mut stream) = self.streams.get(&stream_id) {
+                stream.unpaid_amount += refund;
+                self.streams.insert(&stream_id, &stream);
+                log!("Stream {} refunded {}, accrued again", stream_id, refund);
+            }
+        }
+        used_amount.into()
+    }
+}
+
+impl Contract {
+    /// Pay the accrued amount of the stream as much as the contract holds, and save the rest as unpaid.
+    fn internal_pay_stream(&mut self, stream_id: u32, stream: &mut Stream) -> PromiseOrValue<U128> {
+        let cur_time = cur_time_in_sec();
+        let accrued_amount = stream.accrued_amount(cur_time);
+        let contract_id = env::current_account_id();
+        let amount = min(accrued_amount, self.internal_treasury_balance());
+        stream.unpaid_amount = accrued_amount - amount;
+        stream.accrued_time_in_sec = max(stream.accrued_time_in_sec, min(cur_time, stream.end_time_in_sec));
+        self.streams.insert(&stream_id, stream);
+        if amount == 0 {
+            return PromiseOrValue::Value(U128(0));
+        }
+
+        self.internal_transfer(&contract_id, &stream.receiver_id, amount, Some(format!("stream {}", stream_id)));
+        PromiseOrValue::Promise(
+            self.internal_transfer_call_promise(
+                contract_id.clone(),
+                stream.receiver_id.clone(),
+                amount,
+                stream.msg.clone(),
+                GAS_FOR_FT_ON_TRANSFER,
+            )
+            .then(ext_self::on_stream_paid(
+                stream_id,
+                amount.into(),
+                &contract_id,
+                NO_DEPOSIT,
+                GAS_FOR_ON_STREAM_PAID,
+            )),
+        )
+    }
+}
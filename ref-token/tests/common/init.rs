@@ -0,0 +1,50 @@
+use near_sdk::serde_json::json;
+use near_sdk_sim::{call, deploy, init_simulator, to_yocto, ContractAccount, UserAccount, DEFAULT_GAS};
+use ref_token::ContractContract as RefToken;
+
+near_sdk_sim::lazy_static_include::lazy_static_include_bytes! {
+    REF_WASM_BYTES => "../res/ref_token_release.wasm",
+    XREF_WASM_BYTES => "../res/xref_token_release.wasm",
+}
+
+/// Owner holds all the 10000 REF minted at initialization, user is registered with no REF.
+pub fn init_env() -> (UserAccount, UserAccount, UserAccount, ContractAccount<RefToken>){
+    let root = init_simulator(None);
+
+    let owner = root.create_user("owner".to_string(), to_yocto("100"));
+    let user = root.create_user("user".to_string(), to_yocto("100"));
+
+    let ref_contract = deploy!(
+        contract: RefToken,
+        contract_id: "ref",
+        bytes: &REF_WASM_BYTES,
+        signer_account: root
+    );
+    call!(root, ref_contract.new(owner.valid_account_id(), to_yocto("10000").into())).assert_success();
+    call!(user, ref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+    (root, owner, user, ref_contract)
+}
+
+/// Create a user registered on REF.
+#[allow(dead_code)]
+pub fn create_registered_user(root: &UserAccount, account_id: &str, ref_contract: &ContractAccount<RefToken>) -> UserAccount {
+    let new_user = root.create_user(account_id.to_string(), to_yocto("100"));
+    call!(new_user, ref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+    new_user
+}
+
+/// Deploy xref staking on REF as a receiver of `ft_transfer_call`, registered on REF and not seeded yet.
+#[allow(dead_code)]
+pub fn deploy_xref(root: &UserAccount, owner: &UserAccount, ref_contract: &ContractAccount<RefToken>) -> UserAccount {
+    let xref = root.deploy(&XREF_WASM_BYTES, "xref".to_string(), to_yocto("100"));
+    root.call(
+        xref.account_id(),
+        "new",
+        json!({"owner_id": owner.account_id(), "locked_token": ref_contract.account_id()}).to_string().as_bytes(),
+        DEFAULT_GAS,
+        0,
+    )
+    .assert_success();
+    call!(root, ref_contract.storage_deposit(Some(xref.valid_account_id()), None), deposit = to_yocto("1")).assert_success();
+    xref
+}
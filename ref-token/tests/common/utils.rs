@@ -0,0 +1,28 @@
+#![allow(unused)] 
+use near_sdk::serde_json::{self, Value};
+use near_sdk_sim::ExecutionResult;
+
+pub fn get_error_count(r: &ExecutionResult) -> u32 {
+    r.promise_errors().len() as u32
+}
+
+pub fn get_error_status(r: &ExecutionResult) -> String {
+    format!("{:?}", r.promise_errors()[0].as_ref().unwrap().status())
+}
+
+/// Logs of the call and all the receipts it spawned, in order.
+pub fn get_logs(r: &ExecutionResult) -> Vec<String> {
+    r.promise_results()
+        .iter()
+        .flat_map(|result| result.as_ref().map(|result| result.logs().clone()).unwrap_or_default())
+        .collect()
+}
+
+/// NEP-297 events in the logs of the call and all the receipts it spawned.
+pub fn get_events(r: &ExecutionResult) -> Vec<Value> {
+    get_logs(r)
+        .iter()
+        .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+        .map(|event| serde_json::from_str(event).unwrap())
+        .collect()
+}
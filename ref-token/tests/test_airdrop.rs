@@ -0,0 +1,167 @@
+use near_sdk_sim::{call, view, to_yocto, UserAccount};
+use near_sdk::json_types::{Base64VecU8, U128};
+use ref_token::CampaignInfo;
+use sha2::{Digest, Sha256};
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+fn sha256(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(data).to_vec()
+}
+
+fn leaf_hash(index: u32, account_id: &str, amount: u128) -> Vec<u8> {
+    let mut data = vec![0u8];
+    data.extend_from_slice(&index.to_le_bytes());
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(account_id.as_bytes());
+    sha256(&data)
+}
+
+fn node_hash(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut data = vec![1u8];
+    if a <= b {
+        data.extend_from_slice(a);
+        data.extend_from_slice(b);
+    } else {
+        data.extend_from_slice(b);
+        data.extend_from_slice(a);
+    }
+    sha256(&data)
+}
+
+/// Merkle root of four leaves, and the proof of each leaf.
+fn build_tree(leaves: &[Vec<u8>; 4]) -> (Vec<u8>, Vec<Vec<Base64VecU8>>) {
+    let left = node_hash(&leaves[0], &leaves[1]);
+    let right = node_hash(&leaves[2], &leaves[3]);
+    let root = node_hash(&left, &right);
+    let proofs = (0..4)
+        .map(|i| {
+            let upper = if i < 2 { right.clone() } else { left.clone() };
+            vec![leaves[i ^ 1].clone().into(), upper.into()]
+        })
+        .collect();
+    (root, proofs)
+}
+
+fn balance_of(ref_contract: &near_sdk_sim::ContractAccount<ref_token::ContractContract>, user: &UserAccount) -> u128 {
+    view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0
+}
+
+#[test]
+fn test_airdrop(){
+    let (root, owner, user, ref_contract) =
+        init_env();
+    let user2 = create_registered_user(&root, "user2", &ref_contract);
+    let amounts = [to_yocto("10"), to_yocto("20"), to_yocto("30"), to_yocto("40")];
+    let leaves = [
+        leaf_hash(0, "user", amounts[0]),
+        leaf_hash(1, "user2", amounts[1]),
+        leaf_hash(2, "user3", amounts[2]),
+        leaf_hash(3, "user4", amounts[3]),
+    ];
+    let (merkle_root, proofs) = build_tree(&leaves);
+
+    let current_time = root.borrow_runtime().cur_block.block_timestamp / 1_000_000_000;
+    let expire_time = current_time as u32 + 100;
+    let out_come = call!(
+        user,
+        ref_contract.add_campaign(merkle_root.clone().into(), 4, to_yocto("100").into(), expire_time),
+        deposit = to_yocto("0.01")
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    // the claimed bitmap storage must be paid
+    let out_come = call!(
+        owner,
+        ref_contract.add_campaign(merkle_root.clone().into(), 4, to_yocto("100").into(), expire_time)
+    );
+    assert!(get_error_status(&out_come).contains("ERR_INSUFFICIENT_STORAGE_DEPOSIT"));
+    call!(
+        owner,
+        ref_contract.add_campaign(merkle_root.clone().into(), 4, to_yocto("100").into(), expire_time),
+        deposit = to_yocto("0.01")
+    )
+    .assert_success();
+    let campaign = view!(ref_contract.get_campaign(0)).unwrap_json::<Option<CampaignInfo>>().unwrap();
+    assert_eq!(campaign.entry_count, 4);
+    assert_eq!(campaign.total_amount.0, to_yocto("100"));
+    assert_eq!(balance_of(&ref_contract, &owner), to_yocto("9900"));
+
+    // claim
+    call!(
+        user,
+        ref_contract.claim(0, 0, amounts[0].into(), proofs[0].clone())
+    )
+    .assert_success();
+    assert_eq!(balance_of(&ref_contract, &user), to_yocto("10"));
+    assert!(view!(ref_contract.is_claimed(0, 0)).unwrap_json::<bool>());
+    assert!(!view!(ref_contract.is_claimed(0, 1)).unwrap_json::<bool>());
+
+    // double claim
+    let out_come = call!(
+        user,
+        ref_contract.claim(0, 0, amounts[0].into(), proofs[0].clone())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_ALREADY_CLAIMED"));
+
+    // bad proof: wrong amount, other's entry, missing siblings, index out of range
+    let out_come = call!(
+        user2,
+        ref_contract.claim(0, 1, (amounts[1] + 1).into(), proofs[1].clone())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_INVALID_PROOF"));
+    let out_come = call!(
+        user2,
+        ref_contract.claim(0, 2, amounts[2].into(), proofs[2].clone())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_INVALID_PROOF"));
+    let out_come = call!(
+        user2,
+        ref_contract.claim(0, 3, amounts[3].into(), vec![])
+    );
+    assert!(get_error_status(&out_come).contains("ERR_INVALID_PROOF"));
+    let out_come = call!(
+        user2,
+        ref_contract.claim(0, 4, amounts[1].into(), proofs[1].clone())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_INVALID_INDEX"));
+    assert_eq!(balance_of(&ref_contract, &user2), 0);
+
+    // reclaim
+    let out_come = call!(
+        owner,
+        ref_contract.reclaim_campaign(0)
+    );
+    assert!(get_error_status(&out_come).contains("ERR_CAMPAIGN_NOT_EXPIRED"));
+    call!(
+        user2,
+        ref_contract.claim(0, 1, amounts[1].into(), proofs[1].clone())
+    )
+    .assert_success();
+    root.borrow_runtime_mut().cur_block.block_timestamp = (expire_time as u64 + 1) * 1_000_000_000;
+    let out_come = call!(
+        user,
+        ref_contract.reclaim_campaign(0)
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    let out_come = call!(
+        owner,
+        ref_contract.reclaim_campaign(0)
+    );
+    out_come.assert_success();
+    assert_eq!(out_come.unwrap_json::<U128>().0, to_yocto("70"));
+    assert_eq!(balance_of(&ref_contract, &owner), to_yocto("9970"));
+    assert!(view!(ref_contract.get_campaign(0)).unwrap_json::<Option<CampaignInfo>>().is_none());
+    // the claimed bitmap is cleared
+    assert!(!view!(ref_contract.is_claimed(0, 0)).unwrap_json::<bool>());
+    assert!(!view!(ref_contract.is_claimed(0, 1)).unwrap_json::<bool>());
+
+    let out_come = call!(
+        user2,
+        ref_contract.claim(0, 1, amounts[1].into(), proofs[1].clone())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_CAMPAIGN_NOT_FOUND"));
+}
@@ -0,0 +1,74 @@
+use near_sdk_sim::{call, view, to_yocto};
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+#[test]
+fn test_batch_transfer(){
+    let (root, owner, user, ref_contract) =
+        init_env();
+    let user2 = create_registered_user(&root, "user2", &ref_contract);
+    let stranger = root.create_user("stranger".to_string(), to_yocto("100"));
+
+    // all or nothing, an unregistered receiver fails the whole batch
+    let out_come = call!(
+        owner,
+        ref_contract.ft_batch_transfer(
+            vec![
+                (user.valid_account_id(), to_yocto("10").into()),
+                (stranger.valid_account_id(), to_yocto("20").into()),
+                (user2.valid_account_id(), to_yocto("30").into()),
+            ],
+            None
+        ),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("The account stranger is not registered"));
+    assert!(get_events(&out_come).is_empty());
+    assert_eq!(view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0, to_yocto("10000"));
+    assert_eq!(view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0, 0);
+    assert_eq!(view!(ref_contract.ft_balance_of(user2.valid_account_id())).unwrap_json::<U128>().0, 0);
+
+    let out_come = call!(
+        owner,
+        ref_contract.ft_batch_transfer(
+            vec![
+                (user.valid_account_id(), to_yocto("10").into()),
+                (user2.valid_account_id(), to_yocto("30").into()),
+            ],
+            Some("payroll".to_string())
+        ),
+        deposit = 1
+    );
+    out_come.assert_success();
+    assert_eq!(
+        get_events(&out_come),
+        vec![json!({
+            "standard": "nep141",
+            "version": "1.0.0",
+            "event": "ft_transfer",
+            "data": [
+                {
+                    "old_owner_id": "owner",
+                    "new_owner_id": "user",
+                    "amount": to_yocto("10").to_string(),
+                    "memo": "payroll",
+                },
+                {
+                    "old_owner_id": "owner",
+                    "new_owner_id": "user2",
+                    "amount": to_yocto("30").to_string(),
+                    "memo": "payroll",
+                },
+            ],
+        })]
+    );
+    assert_eq!(view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0, to_yocto("9960"));
+    assert_eq!(view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0, to_yocto("10"));
+    assert_eq!(view!(ref_contract.ft_balance_of(user2.valid_account_id())).unwrap_json::<U128>().0, to_yocto("30"));
+}
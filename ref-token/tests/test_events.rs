@@ -0,0 +1,88 @@
+use near_sdk_sim::{call, view, to_yocto};
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+fn ft_transfer_event(data: Vec<near_sdk::serde_json::Value>) -> near_sdk::serde_json::Value {
+    json!({
+        "standard": "nep141",
+        "version": "1.0.0",
+        "event": "ft_transfer",
+        "data": data,
+    })
+}
+
+#[test]
+fn test_transfer_events(){
+    let (root, owner, user, ref_contract) =
+        init_env();
+
+    let out_come = call!(
+        owner,
+        ref_contract.ft_transfer(user.valid_account_id(), to_yocto("10").into(), Some("hi".to_string())),
+        deposit = 1
+    );
+    out_come.assert_success();
+    assert_eq!(
+        get_events(&out_come),
+        vec![ft_transfer_event(vec![json!({
+            "old_owner_id": "owner",
+            "new_owner_id": "user",
+            "amount": to_yocto("10").to_string(),
+            "memo": "hi",
+        })])]
+    );
+    // the event is the only log, no legacy log of the standard
+    assert_eq!(get_logs(&out_come).len(), 1);
+
+    // nothing is logged for a failed transfer
+    let stranger = root.create_user("stranger".to_string(), to_yocto("100"));
+    let out_come = call!(
+        owner,
+        ref_contract.ft_transfer(stranger.valid_account_id(), to_yocto("10").into(), None),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("The account stranger is not registered"));
+    assert!(get_events(&out_come).is_empty());
+    assert_eq!(view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0, to_yocto("9990"));
+}
+
+#[test]
+fn test_transfer_call_events(){
+    let (root, owner, _, ref_contract) =
+        init_env();
+    // xref rejects stakes until seeded, so the whole amount is refunded
+    let xref = deploy_xref(&root, &owner, &ref_contract);
+
+    let out_come = call!(
+        owner,
+        ref_contract.ft_transfer_call(xref.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    );
+    out_come.assert_success();
+    assert_eq!(out_come.unwrap_json::<U128>().0, 0);
+    assert_eq!(
+        get_events(&out_come),
+        vec![
+            ft_transfer_event(vec![json!({
+                "old_owner_id": "owner",
+                "new_owner_id": "xref",
+                "amount": to_yocto("10").to_string(),
+            })]),
+            ft_transfer_event(vec![json!({
+                "old_owner_id": "xref",
+                "new_owner_id": "owner",
+                "amount": to_yocto("10").to_string(),
+                "memo": "refund",
+            })]),
+        ]
+    );
+    assert!(!get_logs(&out_come).iter().any(|log| log.starts_with("Transfer") || log.starts_with("Refund")));
+    assert_eq!(view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0, to_yocto("10000"));
+    assert_eq!(view!(ref_contract.ft_balance_of(xref.valid_account_id())).unwrap_json::<U128>().0, 0);
+}
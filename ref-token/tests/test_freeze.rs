@@ -0,0 +1,247 @@
+use near_sdk_sim::{call, view, to_yocto};
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+const FREEZE_DURATION_IN_SEC: u64 = 7 * 24 * 3600;
+const FREEZE_RENEWAL_TIMELOCK_IN_SEC: u64 = 2 * 24 * 3600;
+
+#[test]
+fn test_freeze(){
+    let (root, owner, user, ref_contract) =
+        init_env();
+    call!(
+        owner,
+        ref_contract.ft_transfer(user.valid_account_id(), to_yocto("100").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+
+    let out_come = call!(
+        user,
+        ref_contract.freeze(user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+
+    // frozen at once
+    let current_time = root.borrow_runtime().cur_block.block_timestamp / 1_000_000_000;
+    let out_come = call!(
+        owner,
+        ref_contract.freeze(user.valid_account_id())
+    );
+    out_come.assert_success();
+    let expire_in_sec = out_come.unwrap_json::<u32>();
+    assert!(expire_in_sec as u64 >= current_time + FREEZE_DURATION_IN_SEC);
+    assert_eq!(
+        get_events(&out_come),
+        vec![json!({
+            "standard": "ref-token",
+            "version": "1.0.0",
+            "event": "account_frozen",
+            "data": [{"account_id": "user", "expire_in_sec": expire_in_sec}],
+        })]
+    );
+    assert!(view!(ref_contract.is_frozen(user.valid_account_id())).unwrap_json::<bool>());
+    assert_eq!(
+        view!(ref_contract.get_frozen_accounts(0, u64::MAX)).unwrap_json::<Vec<(String, u32)>>(),
+        vec![("user".to_string(), expire_in_sec)]
+    );
+    assert!(view!(ref_contract.get_frozen_accounts(1, u64::MAX)).unwrap_json::<Vec<(String, u32)>>().is_empty());
+
+    let out_come = call!(
+        user,
+        ref_contract.ft_transfer(owner.valid_account_id(), to_yocto("10").into(), None),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_ACCOUNT_FROZEN"));
+    let out_come = call!(
+        user,
+        ref_contract.ft_batch_transfer(vec![(owner.valid_account_id(), to_yocto("10").into())], None),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_ACCOUNT_FROZEN"));
+    // a frozen account can still receive
+    call!(
+        owner,
+        ref_contract.ft_transfer(user.valid_account_id(), to_yocto("10").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+
+    // unfreeze
+    let out_come = call!(
+        user,
+        ref_contract.unfreeze(user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    let out_come = call!(
+        owner,
+        ref_contract.unfreeze(user.valid_account_id())
+    );
+    out_come.assert_success();
+    assert!(get_logs(&out_come).iter().any(|log| log.contains("account_unfrozen")));
+    assert!(!view!(ref_contract.is_frozen(user.valid_account_id())).unwrap_json::<bool>());
+    call!(
+        user,
+        ref_contract.ft_transfer(owner.valid_account_id(), to_yocto("10").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    let out_come = call!(
+        owner,
+        ref_contract.unfreeze(user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_ACCOUNT_NOT_FROZEN"));
+
+    // freezing again within a freeze duration after the freeze ended takes a renewal
+    let out_come = call!(
+        owner,
+        ref_contract.freeze(user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_FREEZE_RENEWAL_REQUIRED"));
+    let out_come = call!(
+        user,
+        ref_contract.schedule_freeze_renewal(user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    let out_come = call!(
+        owner,
+        ref_contract.schedule_freeze_renewal(owner.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_ACCOUNT_NOT_FROZEN"));
+    let current_time = root.borrow_runtime().cur_block.block_timestamp / 1_000_000_000;
+    let out_come = call!(
+        owner,
+        ref_contract.schedule_freeze_renewal(user.valid_account_id())
+    );
+    out_come.assert_success();
+    let eta_in_sec = out_come.unwrap_json::<u32>();
+    assert!(eta_in_sec as u64 >= current_time + FREEZE_RENEWAL_TIMELOCK_IN_SEC);
+    assert_eq!(
+        get_events(&out_come),
+        vec![json!({
+            "standard": "ref-token",
+            "version": "1.0.0",
+            "event": "freeze_renewal_scheduled",
+            "data": [{"account_id": "user", "eta_in_sec": eta_in_sec}],
+        })]
+    );
+    assert_eq!(
+        view!(ref_contract.get_pending_freeze_renewals(0, 100)).unwrap_json::<Vec<(String, u32)>>(),
+        vec![("user".to_string(), eta_in_sec)]
+    );
+    let out_come = call!(
+        owner,
+        ref_contract.schedule_freeze_renewal(user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_FREEZE_RENEWAL_PENDING"));
+    let out_come = call!(
+        owner,
+        ref_contract.execute_freeze_renewal(user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_FREEZE_RENEWAL_TIMELOCKED"));
+
+    // cancel
+    let out_come = call!(
+        user,
+        ref_contract.cancel_freeze_renewal(user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    let out_come = call!(
+        owner,
+        ref_contract.cancel_freeze_renewal(user.valid_account_id())
+    );
+    out_come.assert_success();
+    assert!(get_logs(&out_come).iter().any(|log| log.contains("freeze_renewal_cancelled")));
+    assert!(view!(ref_contract.get_pending_freeze_renewals(0, 100)).unwrap_json::<Vec<(String, u32)>>().is_empty());
+    root.borrow_runtime_mut().cur_block.block_timestamp += FREEZE_RENEWAL_TIMELOCK_IN_SEC * 1_000_000_000;
+    let out_come = call!(
+        owner,
+        ref_contract.execute_freeze_renewal(user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NO_PENDING_FREEZE_RENEWAL"));
+
+    // execute after the timelock freezes again
+    call!(
+        owner,
+        ref_contract.schedule_freeze_renewal(user.valid_account_id())
+    )
+    .assert_success();
+    root.borrow_runtime_mut().cur_block.block_timestamp += FREEZE_RENEWAL_TIMELOCK_IN_SEC * 1_000_000_000;
+    let out_come = call!(
+        user,
+        ref_contract.execute_freeze_renewal(user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    let out_come = call!(
+        owner,
+        ref_contract.execute_freeze_renewal(user.valid_account_id())
+    );
+    out_come.assert_success();
+    let expire_in_sec = out_come.unwrap_json::<u32>();
+    assert_eq!(
+        get_events(&out_come),
+        vec![json!({
+            "standard": "ref-token",
+            "version": "1.0.0",
+            "event": "freeze_renewed",
+            "data": [{"account_id": "user", "expire_in_sec": expire_in_sec}],
+        })]
+    );
+    assert!(view!(ref_contract.is_frozen(user.valid_account_id())).unwrap_json::<bool>());
+    let out_come = call!(
+        user,
+        ref_contract.ft_transfer(owner.valid_account_id(), to_yocto("10").into(), None),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_ACCOUNT_FROZEN"));
+
+    // a renewal of an ongoing freeze extends it
+    call!(
+        owner,
+        ref_contract.schedule_freeze_renewal(user.valid_account_id())
+    )
+    .assert_success();
+    root.borrow_runtime_mut().cur_block.block_timestamp += FREEZE_RENEWAL_TIMELOCK_IN_SEC * 1_000_000_000;
+    let out_come = call!(
+        owner,
+        ref_contract.execute_freeze_renewal(user.valid_account_id())
+    );
+    assert_eq!(out_come.unwrap_json::<u32>() as u64, expire_in_sec as u64 + FREEZE_DURATION_IN_SEC);
+    let expire_in_sec = expire_in_sec as u64 + FREEZE_DURATION_IN_SEC;
+
+    // the freeze expires by itself
+    root.borrow_runtime_mut().cur_block.block_timestamp = expire_in_sec * 1_000_000_000;
+    assert!(!view!(ref_contract.is_frozen(user.valid_account_id())).unwrap_json::<bool>());
+    call!(
+        user,
+        ref_contract.ft_transfer(owner.valid_account_id(), to_yocto("10").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0, to_yocto("90"));
+
+    // a freeze duration after, it can be frozen at once again, and unfreeze drops the pending renewal
+    root.borrow_runtime_mut().cur_block.block_timestamp += FREEZE_DURATION_IN_SEC * 1_000_000_000;
+    call!(
+        owner,
+        ref_contract.freeze(user.valid_account_id())
+    )
+    .assert_success();
+    call!(
+        owner,
+        ref_contract.schedule_freeze_renewal(user.valid_account_id())
+    )
+    .assert_success();
+    call!(
+        owner,
+        ref_contract.unfreeze(user.valid_account_id())
+    )
+    .assert_success();
+    assert!(view!(ref_contract.get_pending_freeze_renewals(0, 100)).unwrap_json::<Vec<(String, u32)>>().is_empty());
+}
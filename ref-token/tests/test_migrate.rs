@@ -0,0 +1,127 @@
+use near_sdk_sim::{call, deploy, view, init_simulator, to_yocto, DEFAULT_GAS};
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+use near_contract_standards::fungible_token::metadata::FungibleTokenMetadata;
+use ref_token::ContractContract as RefToken;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+near_sdk_sim::lazy_static_include::lazy_static_include_bytes! {
+    PREV_REF_WASM_BYTES => "../res/ref_token_v100_release.wasm",
+    REF_WASM_BYTES => "../res/ref_token_release.wasm",
+}
+
+#[test]
+fn test_migrate_from_v100() {
+    let root = init_simulator(None);
+    let owner = root.create_user("owner".to_string(), to_yocto("100"));
+    let user = root.create_user("user".to_string(), to_yocto("100"));
+    let ref_contract = deploy!(
+        contract: RefToken,
+        contract_id: "ref".to_string(),
+        bytes: &PREV_REF_WASM_BYTES,
+        signer_account: root,
+        init_method: new(owner.valid_account_id(), to_yocto("10000").into())
+    );
+    call!(user, ref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+    call!(
+        owner,
+        ref_contract.ft_transfer(user.valid_account_id(), to_yocto("100").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+
+    // 1.0.0 has no upgrade method, the code is deployed with the key of the contract account
+    ref_contract
+        .user_account
+        .create_transaction(ref_contract.account_id())
+        .deploy_contract(REF_WASM_BYTES.to_vec())
+        .function_call(
+            "migrate".to_string(),
+            json!({"owner_id": owner.account_id()}).to_string().into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .submit()
+        .assert_success();
+
+    assert_eq!(view!(ref_contract.get_version()).unwrap_json::<String>(), "1.1.0");
+    assert_eq!(view!(ref_contract.get_owner()).unwrap_json::<String>(), "owner");
+    assert!(view!(ref_contract.get_pending_owner()).unwrap_json::<Option<String>>().is_none());
+    assert_eq!(view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0, to_yocto("9900"));
+    assert_eq!(view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0, to_yocto("100"));
+    assert_eq!(view!(ref_contract.ft_total_supply()).unwrap_json::<U128>().0, to_yocto("10000"));
+    assert_eq!(view!(ref_contract.ft_metadata()).unwrap_json::<FungibleTokenMetadata>().symbol, "REF");
+    assert!(view!(ref_contract.get_max_supply()).unwrap_json::<Option<U128>>().is_none());
+
+    // the migrated contract works on
+    call!(
+        user,
+        ref_contract.ft_transfer(owner.valid_account_id(), to_yocto("10").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0, to_yocto("9910"));
+    call!(
+        owner,
+        ref_contract.set_max_supply(to_yocto("20000").into())
+    )
+    .assert_success();
+
+    // only the contract itself can migrate
+    let out_come = call!(
+        user,
+        ref_contract.migrate(user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("is private"));
+    assert_eq!(view!(ref_contract.get_owner()).unwrap_json::<String>(), "owner");
+}
+
+#[test]
+fn test_upgrade() {
+    let (_, owner, user, ref_contract) =
+        init_env();
+    call!(
+        owner,
+        ref_contract.ft_transfer(user.valid_account_id(), to_yocto("100").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        owner,
+        ref_contract.set_max_supply(to_yocto("20000").into())
+    )
+    .assert_success();
+
+    // Failed upgrade with no permissions.
+    let result = user
+        .call(
+            ref_contract.user_account.account_id.clone(),
+            "upgrade",
+            &REF_WASM_BYTES,
+            DEFAULT_GAS,
+            0,
+        )
+        .status();
+    assert!(format!("{:?}", result).contains("ERR_NOT_ALLOWED"));
+
+    // Upgrade to the same state layout keeps the state and the owner.
+    owner
+        .call(
+            ref_contract.user_account.account_id.clone(),
+            "upgrade",
+            &REF_WASM_BYTES,
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    assert_eq!(view!(ref_contract.get_owner()).unwrap_json::<String>(), "owner");
+    assert_eq!(view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0, to_yocto("9900"));
+    assert_eq!(view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0, to_yocto("100"));
+    assert_eq!(view!(ref_contract.ft_total_supply()).unwrap_json::<U128>().0, to_yocto("10000"));
+    assert_eq!(view!(ref_contract.get_max_supply()).unwrap_json::<Option<U128>>().unwrap().0, to_yocto("20000"));
+}
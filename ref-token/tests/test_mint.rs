@@ -0,0 +1,157 @@
+use near_sdk_sim::{call, view, to_yocto};
+use near_sdk::json_types::U128;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+#[test]
+fn test_minter_allowance(){
+    let (_, owner, user, ref_contract) =
+        init_env();
+
+    let out_come = call!(
+        user,
+        ref_contract.set_minter_allowance(user.valid_account_id(), to_yocto("200").into())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    call!(
+        owner,
+        ref_contract.set_minter_allowance(user.valid_account_id(), to_yocto("200").into())
+    )
+    .assert_success();
+    assert_eq!(view!(ref_contract.get_minter_allowance(user.valid_account_id())).unwrap_json::<U128>().0, to_yocto("200"));
+
+    // minting is off until max supply is set
+    let out_come = call!(
+        user,
+        ref_contract.mint(user.valid_account_id(), to_yocto("50").into())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_MAX_SUPPLY_NOT_SET"));
+    call!(
+        owner,
+        ref_contract.set_max_supply(to_yocto("20000").into())
+    )
+    .assert_success();
+
+    let out_come = call!(
+        owner,
+        ref_contract.mint(owner.valid_account_id(), to_yocto("50").into())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_MINTER"));
+    let out_come = call!(
+        user,
+        ref_contract.mint(user.valid_account_id(), to_yocto("201").into())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_MINTER_ALLOWANCE_EXCEEDED"));
+
+    call!(
+        user,
+        ref_contract.mint(user.valid_account_id(), to_yocto("50").into())
+    )
+    .assert_success();
+    assert_eq!(view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0, to_yocto("50"));
+    assert_eq!(view!(ref_contract.get_minter_allowance(user.valid_account_id())).unwrap_json::<U128>().0, to_yocto("150"));
+    assert_eq!(view!(ref_contract.ft_total_supply()).unwrap_json::<U128>().0, to_yocto("10050"));
+
+    // minting up the whole allowance removes the minter
+    call!(
+        user,
+        ref_contract.mint(owner.valid_account_id(), to_yocto("150").into())
+    )
+    .assert_success();
+    assert!(view!(ref_contract.get_minters()).unwrap_json::<Vec<(String, U128)>>().is_empty());
+    let out_come = call!(
+        user,
+        ref_contract.mint(user.valid_account_id(), to_yocto("1").into())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_MINTER"));
+
+    // and so does zero allowance
+    call!(
+        owner,
+        ref_contract.set_minter_allowance(user.valid_account_id(), to_yocto("10").into())
+    )
+    .assert_success();
+    assert_eq!(view!(ref_contract.get_minters()).unwrap_json::<Vec<(String, U128)>>(), vec![("user".to_string(), U128(to_yocto("10")))]);
+    call!(
+        owner,
+        ref_contract.set_minter_allowance(user.valid_account_id(), U128(0))
+    )
+    .assert_success();
+    assert!(view!(ref_contract.get_minters()).unwrap_json::<Vec<(String, U128)>>().is_empty());
+}
+
+#[test]
+fn test_max_supply(){
+    let (_, owner, user, ref_contract) =
+        init_env();
+    call!(
+        owner,
+        ref_contract.set_minter_allowance(user.valid_account_id(), to_yocto("1000").into())
+    )
+    .assert_success();
+
+    let out_come = call!(
+        owner,
+        ref_contract.set_max_supply(to_yocto("9999").into())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_MAX_SUPPLY_BELOW_SUPPLY"));
+    call!(
+        owner,
+        ref_contract.set_max_supply(to_yocto("10100").into())
+    )
+    .assert_success();
+    let out_come = call!(
+        owner,
+        ref_contract.set_max_supply(to_yocto("10101").into())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_MAX_SUPPLY_CAN_ONLY_DECREASE"));
+    assert_eq!(view!(ref_contract.get_max_supply()).unwrap_json::<Option<U128>>(), Some(U128(to_yocto("10100"))));
+
+    call!(
+        user,
+        ref_contract.mint(user.valid_account_id(), to_yocto("50").into())
+    )
+    .assert_success();
+    let out_come = call!(
+        user,
+        ref_contract.mint(user.valid_account_id(), to_yocto("51").into())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_EXCEED_MAX_SUPPLY"));
+
+    // burned token still counts against the cap, so it can't be minted again
+    call!(
+        user,
+        ref_contract.ft_burn(to_yocto("30").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(view!(ref_contract.ft_total_supply()).unwrap_json::<U128>().0, to_yocto("10020"));
+    assert_eq!(view!(ref_contract.get_burned_supply()).unwrap_json::<U128>().0, to_yocto("30"));
+    let out_come = call!(
+        user,
+        ref_contract.mint(user.valid_account_id(), to_yocto("51").into())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_EXCEED_MAX_SUPPLY"));
+    // the cap can't go below the issued supply either, even above the current supply
+    let out_come = call!(
+        owner,
+        ref_contract.set_max_supply(to_yocto("10040").into())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_MAX_SUPPLY_BELOW_SUPPLY"));
+
+    call!(
+        user,
+        ref_contract.mint(user.valid_account_id(), to_yocto("50").into())
+    )
+    .assert_success();
+    assert_eq!(view!(ref_contract.ft_total_supply()).unwrap_json::<U128>().0, to_yocto("10070"));
+    let out_come = call!(
+        user,
+        ref_contract.mint(user.valid_account_id(), U128(1))
+    );
+    assert!(get_error_status(&out_come).contains("ERR_EXCEED_MAX_SUPPLY"));
+}
@@ -0,0 +1,118 @@
+use near_sdk_sim::{call, view, to_yocto};
+use near_sdk::json_types::Base64VecU8;
+use near_contract_standards::fungible_token::metadata::FungibleTokenMetadata;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+#[test]
+fn test_owner_transfer(){
+    let (_, owner, user, ref_contract) =
+        init_env();
+
+    let out_come = call!(
+        user,
+        ref_contract.propose_owner(user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    let out_come = call!(
+        user,
+        ref_contract.accept_owner()
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NO_PENDING_OWNER"));
+
+    call!(
+        owner,
+        ref_contract.propose_owner(user.valid_account_id())
+    )
+    .assert_success();
+    assert_eq!(view!(ref_contract.get_pending_owner()).unwrap_json::<Option<String>>(), Some("user".to_string()));
+    // ownership doesn't change until accepted
+    assert_eq!(view!(ref_contract.get_owner()).unwrap_json::<String>(), "owner");
+    let out_come = call!(
+        owner,
+        ref_contract.accept_owner()
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+
+    // cancel
+    let out_come = call!(
+        user,
+        ref_contract.cancel_owner_proposal()
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    call!(
+        owner,
+        ref_contract.cancel_owner_proposal()
+    )
+    .assert_success();
+    assert!(view!(ref_contract.get_pending_owner()).unwrap_json::<Option<String>>().is_none());
+    let out_come = call!(
+        user,
+        ref_contract.accept_owner()
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NO_PENDING_OWNER"));
+
+    // propose and accept
+    call!(
+        owner,
+        ref_contract.propose_owner(user.valid_account_id())
+    )
+    .assert_success();
+    call!(
+        user,
+        ref_contract.accept_owner()
+    )
+    .assert_success();
+    assert_eq!(view!(ref_contract.get_owner()).unwrap_json::<String>(), "user");
+    assert!(view!(ref_contract.get_pending_owner()).unwrap_json::<Option<String>>().is_none());
+    let out_come = call!(
+        owner,
+        ref_contract.set_max_supply(to_yocto("20000").into())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    call!(
+        user,
+        ref_contract.set_max_supply(to_yocto("20000").into())
+    )
+    .assert_success();
+}
+
+#[test]
+fn test_metadata(){
+    let (_, owner, user, ref_contract) =
+        init_env();
+    let metadata = view!(ref_contract.ft_metadata()).unwrap_json::<FungibleTokenMetadata>();
+    assert_eq!(metadata.symbol, "REF");
+    assert!(metadata.reference.is_none());
+
+    let out_come = call!(user, ref_contract.set_icon(None));
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    call!(owner, ref_contract.set_icon(Some("data:image/svg+xml;base64,AAAA".to_string()))).assert_success();
+    let metadata = view!(ref_contract.ft_metadata()).unwrap_json::<FungibleTokenMetadata>();
+    assert_eq!(metadata.icon, Some("data:image/svg+xml;base64,AAAA".to_string()));
+
+    let reference = Some("https://ref.finance/ref.json".to_string());
+    let out_come = call!(user, ref_contract.set_reference(reference.clone(), Some(Base64VecU8(vec![1; 32]))));
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    let out_come = call!(owner, ref_contract.set_reference(reference.clone(), None));
+    assert!(get_error_status(&out_come).contains("ERR_REFERENCE_WITHOUT_HASH"));
+    let out_come = call!(owner, ref_contract.set_reference(None, Some(Base64VecU8(vec![1; 32]))));
+    assert!(get_error_status(&out_come).contains("ERR_REFERENCE_WITHOUT_HASH"));
+    let out_come = call!(owner, ref_contract.set_reference(reference.clone(), Some(Base64VecU8(vec![1; 31]))));
+    assert!(get_error_status(&out_come).contains("ERR_INVALID_REFERENCE_HASH"));
+    call!(owner, ref_contract.set_reference(reference.clone(), Some(Base64VecU8(vec![1; 32])))).assert_success();
+    let metadata = view!(ref_contract.ft_metadata()).unwrap_json::<FungibleTokenMetadata>();
+    assert_eq!(metadata.reference, reference);
+    assert_eq!(metadata.reference_hash.unwrap().0, vec![1; 32]);
+    assert_eq!(metadata.icon, Some("data:image/svg+xml;base64,AAAA".to_string()));
+
+    // clear both
+    call!(owner, ref_contract.set_reference(None, None)).assert_success();
+    let metadata = view!(ref_contract.ft_metadata()).unwrap_json::<FungibleTokenMetadata>();
+    assert!(metadata.reference.is_none());
+    assert!(metadata.reference_hash.is_none());
+}
@@ -0,0 +1,174 @@
+use near_sdk_sim::{call, view, to_yocto, ContractAccount, UserAccount};
+use near_sdk::json_types::U128;
+use ref_token::ContractContract as RefToken;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+fn balance_of_at(ref_contract: &ContractAccount<RefToken>, user: &UserAccount, snapshot_id: u32) -> u128 {
+    view!(ref_contract.balance_of_at(user.valid_account_id(), snapshot_id)).unwrap_json::<U128>().0
+}
+
+fn total_supply_at(ref_contract: &ContractAccount<RefToken>, snapshot_id: u32) -> u128 {
+    view!(ref_contract.total_supply_at(snapshot_id)).unwrap_json::<U128>().0
+}
+
+#[test]
+fn test_snapshot(){
+    let (root, owner, user, ref_contract) =
+        init_env();
+    let user2 = create_registered_user(&root, "user2", &ref_contract);
+
+    let out_come = call!(
+        user,
+        ref_contract.snapshot()
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    call!(
+        owner,
+        ref_contract.add_snapshotter(user.valid_account_id())
+    )
+    .assert_success();
+    let out_come = call!(
+        owner,
+        ref_contract.snapshot()
+    );
+    out_come.assert_success();
+    assert_eq!(out_come.unwrap_json::<u32>(), 1);
+
+    // transfer, mint and burn between snapshot 1 and 2
+    call!(
+        owner,
+        ref_contract.ft_transfer(user.valid_account_id(), to_yocto("100").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        owner,
+        ref_contract.set_max_supply(to_yocto("20000").into())
+    )
+    .assert_success();
+    call!(
+        owner,
+        ref_contract.set_minter_allowance(owner.valid_account_id(), to_yocto("50").into())
+    )
+    .assert_success();
+    call!(
+        owner,
+        ref_contract.mint(user2.valid_account_id(), to_yocto("50").into())
+    )
+    .assert_success();
+    call!(
+        user,
+        ref_contract.ft_burn(to_yocto("10").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    // the checkpoints since snapshot 1 are paid by snapshot 2
+    let storage_cost = view!(ref_contract.get_snapshot_storage_cost()).unwrap_json::<U128>().0;
+    assert!(storage_cost > 0);
+    let out_come = call!(
+        user,
+        ref_contract.snapshot(),
+        deposit = storage_cost - 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_INSUFFICIENT_STORAGE_DEPOSIT"));
+    let out_come = call!(
+        user,
+        ref_contract.snapshot(),
+        deposit = storage_cost
+    );
+    out_come.assert_success();
+    assert_eq!(out_come.unwrap_json::<u32>(), 2);
+    assert_eq!(view!(ref_contract.get_snapshot_storage_cost()).unwrap_json::<U128>().0, 0);
+
+    // unregister and transfer after snapshot 2
+    call!(
+        user2,
+        ref_contract.storage_unregister(Some(true)),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        user,
+        ref_contract.ft_transfer(owner.valid_account_id(), to_yocto("40").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+
+    assert_eq!(balance_of_at(&ref_contract, &owner, 1), to_yocto("10000"));
+    assert_eq!(balance_of_at(&ref_contract, &user, 1), 0);
+    assert_eq!(balance_of_at(&ref_contract, &user2, 1), 0);
+    assert_eq!(total_supply_at(&ref_contract, 1), to_yocto("10000"));
+
+    assert_eq!(balance_of_at(&ref_contract, &owner, 2), to_yocto("9900"));
+    assert_eq!(balance_of_at(&ref_contract, &user, 2), to_yocto("90"));
+    assert_eq!(balance_of_at(&ref_contract, &user2, 2), to_yocto("50"));
+    assert_eq!(total_supply_at(&ref_contract, 2), to_yocto("10040"));
+
+    assert_eq!(view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0, to_yocto("9940"));
+    assert_eq!(view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0, to_yocto("50"));
+    assert_eq!(view!(ref_contract.ft_total_supply()).unwrap_json::<U128>().0, to_yocto("9990"));
+
+    let out_come = call!(
+        owner,
+        ref_contract.get_snapshot_timestamp(3)
+    );
+    assert!(get_error_status(&out_come).contains("ERR_INVALID_SNAPSHOT_ID"));
+    let out_come = call!(
+        owner,
+        ref_contract.total_supply_at(0)
+    );
+    assert!(get_error_status(&out_come).contains("ERR_INVALID_SNAPSHOT_ID"));
+}
+
+#[test]
+fn test_snapshot_window(){
+    let (_, owner, user, ref_contract) =
+        init_env();
+    for snapshot_id in 1..=12 {
+        let storage_cost = view!(ref_contract.get_snapshot_storage_cost()).unwrap_json::<U128>().0;
+        // once the window is full, the checkpoints pruned make room for the new ones
+        if snapshot_id == 1 || snapshot_id > 11 {
+            assert_eq!(storage_cost, 0);
+        } else {
+            assert!(storage_cost > 0);
+        }
+        let out_come = call!(
+            owner,
+            ref_contract.snapshot(),
+            deposit = storage_cost
+        );
+        assert_eq!(out_come.unwrap_json::<u32>(), snapshot_id);
+        call!(
+            owner,
+            ref_contract.ft_transfer(user.valid_account_id(), to_yocto("1").into(), None),
+            deposit = 1
+        )
+        .assert_success();
+    }
+    assert_eq!(view!(ref_contract.get_current_snapshot_id()).unwrap_json::<u32>(), 12);
+
+    // only the latest 10 snapshots can be queried
+    for snapshot_id in 1..=2 {
+        let out_come = call!(
+            owner,
+            ref_contract.balance_of_at(owner.valid_account_id(), snapshot_id)
+        );
+        assert!(get_error_status(&out_come).contains("ERR_SNAPSHOT_EXPIRED"));
+        let out_come = call!(
+            owner,
+            ref_contract.get_snapshot_timestamp(snapshot_id)
+        );
+        assert!(get_error_status(&out_come).contains("ERR_SNAPSHOT_EXPIRED"));
+    }
+    for snapshot_id in 3..=12 {
+        assert_eq!(balance_of_at(&ref_contract, &owner, snapshot_id), to_yocto("10001") - to_yocto("1") * snapshot_id as u128);
+        assert_eq!(balance_of_at(&ref_contract, &user, snapshot_id), to_yocto("1") * (snapshot_id - 1) as u128);
+        assert_eq!(total_supply_at(&ref_contract, snapshot_id), to_yocto("10000"));
+    }
+    assert!(view!(ref_contract.get_snapshot_timestamp(3)).unwrap_json::<u64>() < view!(ref_contract.get_snapshot_timestamp(12)).unwrap_json::<u64>());
+}
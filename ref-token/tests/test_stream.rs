@@ -0,0 +1,149 @@
+use near_sdk_sim::{call, view, to_yocto, ContractAccount, UserAccount};
+use near_sdk::json_types::U128;
+use ref_token::{ContractContract as RefToken, StreamInfo};
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+/// Deploy xref staking on REF as the stream receiver, seeded by owner so that it accepts reward.
+fn deploy_seeded_xref(root: &UserAccount, owner: &UserAccount, ref_contract: &ContractAccount<RefToken>) -> UserAccount {
+    let xref = deploy_xref(root, owner, ref_contract);
+    call!(
+        owner,
+        ref_contract.ft_transfer_call(xref.valid_account_id(), to_yocto("1").into(), None, "{\"action\": \"seed_dead_shares\"}".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    xref
+}
+
+#[test]
+fn test_stream(){
+    let (root, owner, _, ref_contract) =
+        init_env();
+    let xref = deploy_seeded_xref(&root, &owner, &ref_contract);
+
+    let current_time = root.borrow_runtime().cur_block.block_timestamp / 1_000_000_000;
+    let start_time = current_time as u32 + 10;
+    let out_come = call!(
+        owner,
+        ref_contract.add_stream(xref.valid_account_id(), "reward".to_string(), U128(u128::MAX / 50), start_time, start_time + 100)
+    );
+    assert!(get_error_status(&out_come).contains("ERR_STREAM_OVERFLOW"));
+    call!(
+        owner,
+        ref_contract.add_stream(xref.valid_account_id(), "reward".to_string(), to_yocto("1").into(), start_time, start_time + 100)
+    )
+    .assert_success();
+
+    // past the end, the whole stream is accrued
+    root.borrow_runtime_mut().cur_block.block_timestamp = (start_time as u64 + 1000) * 1_000_000_000;
+    let stream = view!(ref_contract.get_stream(0)).unwrap_json::<Option<StreamInfo>>().unwrap();
+    assert_eq!(stream.accrued_amount.0, to_yocto("100"));
+
+    // nothing is paid before the contract is funded
+    call!(root, ref_contract.pay_stream(0)).assert_success();
+    assert_eq!(view!(ref_contract.ft_balance_of(xref.valid_account_id())).unwrap_json::<U128>().0, to_yocto("1"));
+
+    // the contract account should register before it can be funded
+    let out_come = call!(
+        owner,
+        ref_contract.ft_transfer(ref_contract.valid_account_id(), to_yocto("60").into(), None),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("is not registered"));
+    call!(owner, ref_contract.storage_deposit(Some(ref_contract.valid_account_id()), None), deposit = to_yocto("1")).assert_success();
+    call!(
+        owner,
+        ref_contract.ft_transfer(ref_contract.valid_account_id(), to_yocto("60").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+
+    // paid as much as funded, the rest stays accrued
+    call!(root, ref_contract.pay_stream(0)).assert_success();
+    assert_eq!(view!(ref_contract.ft_balance_of(xref.valid_account_id())).unwrap_json::<U128>().0, to_yocto("61"));
+    assert_eq!(view!(ref_contract.ft_balance_of(ref_contract.valid_account_id())).unwrap_json::<U128>().0, 0);
+    let stream = view!(ref_contract.get_stream(0)).unwrap_json::<Option<StreamInfo>>().unwrap();
+    assert_eq!(stream.accrued_amount.0, to_yocto("40"));
+
+    // removing pays out what is accrued as far as funded, and drops the rest
+    call!(
+        owner,
+        ref_contract.ft_transfer(ref_contract.valid_account_id(), to_yocto("30").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    let out_come = call!(root, ref_contract.remove_stream(0));
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    let out_come = call!(owner, ref_contract.remove_stream(0));
+    out_come.assert_success();
+    assert!(out_come.logs().iter().any(|log| log.contains(&format!("Stream 0 removed with {} unpaid", to_yocto("10")))));
+    assert_eq!(view!(ref_contract.ft_balance_of(xref.valid_account_id())).unwrap_json::<U128>().0, to_yocto("91"));
+    assert!(view!(ref_contract.get_stream(0)).unwrap_json::<Option<StreamInfo>>().is_none());
+}
+
+#[test]
+fn test_stream_refund(){
+    let (root, owner, user, ref_contract) =
+        init_env();
+    // not seeded, so xref refunds the reward
+    let xref = deploy_xref(&root, &owner, &ref_contract);
+    call!(owner, ref_contract.storage_deposit(Some(ref_contract.valid_account_id()), None), deposit = to_yocto("1")).assert_success();
+    call!(
+        owner,
+        ref_contract.ft_transfer(ref_contract.valid_account_id(), to_yocto("100").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+
+    let current_time = root.borrow_runtime().cur_block.block_timestamp / 1_000_000_000;
+    let start_time = current_time as u32 + 10;
+    let out_come = call!(
+        owner,
+        ref_contract.add_stream(ref_contract.valid_account_id(), "reward".to_string(), to_yocto("1").into(), start_time, start_time + 100)
+    );
+    assert!(get_error_status(&out_come).contains("ERR_INVALID_STREAM_RECEIVER"));
+    call!(
+        owner,
+        ref_contract.add_stream(xref.valid_account_id(), "reward".to_string(), to_yocto("1").into(), start_time, start_time + 100)
+    )
+    .assert_success();
+    call!(
+        owner,
+        ref_contract.add_stream(user.valid_account_id(), "reward".to_string(), to_yocto("1").into(), start_time, start_time + 100)
+    )
+    .assert_success();
+    root.borrow_runtime_mut().cur_block.block_timestamp = (start_time as u64 + 1000) * 1_000_000_000;
+
+    // the refunded payment is back in the treasury and accrued again
+    let out_come = call!(root, ref_contract.pay_stream(0));
+    out_come.assert_success();
+    assert!(get_logs(&out_come).iter().any(|log| log.contains(&format!("Stream 0 refunded {}, accrued again", to_yocto("100")))));
+    assert_eq!(view!(ref_contract.ft_balance_of(xref.valid_account_id())).unwrap_json::<U128>().0, 0);
+    assert_eq!(view!(ref_contract.ft_balance_of(ref_contract.valid_account_id())).unwrap_json::<U128>().0, to_yocto("100"));
+    let stream = view!(ref_contract.get_stream(0)).unwrap_json::<Option<StreamInfo>>().unwrap();
+    assert_eq!(stream.accrued_amount.0, to_yocto("100"));
+
+    // once seeded, xref accepts the payment
+    call!(
+        owner,
+        ref_contract.ft_transfer_call(xref.valid_account_id(), to_yocto("1").into(), None, "{\"action\": \"seed_dead_shares\"}".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    call!(root, ref_contract.pay_stream(0)).assert_success();
+    assert_eq!(view!(ref_contract.ft_balance_of(xref.valid_account_id())).unwrap_json::<U128>().0, to_yocto("101"));
+    let stream = view!(ref_contract.get_stream(0)).unwrap_json::<Option<StreamInfo>>().unwrap();
+    assert_eq!(stream.accrued_amount.0, 0);
+
+    // removal succeeds after the receiver unregistered, dropping what is accrued
+    call!(user, ref_contract.storage_unregister(None), deposit = 1).assert_success();
+    let out_come = call!(owner, ref_contract.remove_stream(1));
+    out_come.assert_success();
+    assert!(out_come.logs().iter().any(|log| log.contains(&format!("Stream 1 removed with {} unpaid", to_yocto("100")))));
+    assert!(view!(ref_contract.get_stream(1)).unwrap_json::<Option<StreamInfo>>().is_none());
+}
@@ -0,0 +1,114 @@
+//! Optional allowance extension on top of NEP-141,
+//! so that a spender can pull xREF from an owner without a `ft_transfer_call` round trip.
+//! The storage of each (owner, spender) allowance is paid by the owner.
+
+use crate::*;
+use near_sdk::{assert_one_yocto, log, Promise, StorageUsage};
+
+/// Charge the storage used since `prev_storage` to the attached deposit,
+/// or pay back the allowance storage released since then,
+/// and refund the rest to the predecessor.
+fn settle_allowance_storage_deposit(prev_storage: StorageUsage) {
+    let cur_storage = env::storage_usage();
+    let attached_deposit = env::attached_deposit();
+    let refund = if cur_storage >= prev_storage {
+        let storage_cost = (cur_storage - prev_storage) as Balance * env::storage_byte_cost();
+        assert!(
+            attached_deposit >= storage_cost,
+            "ERR_INSUFFICIENT_STORAGE_DEPOSIT"
+        );
+        attached_deposit - storage_cost
+    } else {
+        attached_deposit + (prev_storage - cur_storage) as Balance * env::storage_byte_cost()
+    };
+    if refund > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+    }
+}
+
+impl Contract {
+    /// Clear all allowances of an unregistering owner, and pay back their storage to the owner.
+    pub(crate) fn internal_remove_allowances(&mut self, owner_id: &AccountId) {
+        let prev_storage = env::storage_usage();
+        if let Some(spender_ids) = self.allowance_spenders.remove(owner_id) {
+            for spender_id in spender_ids {
+                self.allowances.remove(&(owner_id.clone(), spender_id));
+            }
+        }
+        let released = prev_storage.saturating_sub(env::storage_usage());
+        if released > 0 {
+            Promise::new(owner_id.clone()).transfer(released as Balance * env::storage_byte_cost());
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Allow `spender_id` to transfer up to `amount` of predecessor's xREF,
+    /// replacing the previous allowance. Set `amount` to 0 to revoke,
+    /// which also pays back the storage of the allowance.
+    /// Requirements:
+    /// * The predecessor account should be registered.
+    /// * Requires attached deposit to cover the storage of a new allowance, the extra would be refunded.
+    #[payable]
+    pub fn ft_approve(&mut self, spender_id: ValidAccountId, amount: U128) {
+        assert!(env::attached_deposit() > 0, "ERR_REQUIRE_ATTACHED_DEPOSIT");
+        let prev_storage = env::storage_usage();
+        let owner_id = env::predecessor_account_id();
+        let spender_id: AccountId = spender_id.into();
+        assert!(self.ft.accounts.contains_key(&owner_id), "Account not registered.");
+        assert_ne!(owner_id, spender_id, "ERR_SELF_APPROVE");
+        let amount: Balance = amount.into();
+        let key = (owner_id, spender_id);
+        let mut spender_ids = self.allowance_spenders.get(&key.0).unwrap_or_default();
+        if amount == 0 {
+            if self.allowances.remove(&key).is_some() {
+                spender_ids.retain(|spender_id| spender_id != &key.1);
+            }
+        } else if self.allowances.insert(&key, &amount).is_none() {
+            spender_ids.push(key.1.clone());
+        }
+        if spender_ids.is_empty() {
+            self.allowance_spenders.remove(&key.0);
+        } else {
+            self.allowance_spenders.insert(&key.0, &spender_ids);
+        }
+        log!("{} approve {} token to {}", key.0, amount, key.1);
+
+        settle_allowance_storage_deposit(prev_storage);
+    }
+
+    /// Return the amount of xREF `spender_id` can still transfer from `owner_id`.
+    pub fn ft_allowance(&self, owner_id: ValidAccountId, spender_id: ValidAccountId) -> U128 {
+        self.allowances
+            .get(&(owner_id.into(), spender_id.into()))
+            .unwrap_or(0)
+            .into()
+    }
+
+    /// Transfer `amount` of xREF from `owner_id` to `receiver_id` within the allowance
+    /// the owner gave to the predecessor.
+    /// Requirements:
+    /// * The allowance should be at least `amount`.
+    /// * The owner should have at least `amount` of unlocked xREF.
+    /// * Requires attached deposit of exactly 1 yoctoNEAR.
+    #[payable]
+    pub fn ft_transfer_from(
+        &mut self,
+        owner_id: ValidAccountId,
+        receiver_id: ValidAccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        let key: (AccountId, AccountId) = (owner_id.into(), env::predecessor_account_id());
+        let amount: Balance = amount.into();
+        let allowance = self.allowances.get(&key).unwrap_or(0);
+        assert!(allowance >= amount, "ERR_ALLOWANCE_EXCEEDED");
+        self.assert_available_balance(&key.0, amount);
+        self.internal_record_transfer(&key.0, receiver_id.as_ref(), amount);
+        self.ft.internal_transfer(&key.0, receiver_id.as_ref(), amount, memo);
+        // keep the entry even if it drops to zero, its storage is paid back to owner when revoke.
+        self.allowances.insert(&key, &(allowance - amount));
+    }
+}
@@ -0,0 +1,106 @@
+//! Per-account earnings of holding xREF, in REF.
+//!
+//! Each account keeps a cost basis in REF: staking adds the staked REF,
+//! receiving xREF adds its value at the current price, while unstaking or sending
+//! xREF takes out the proportional part of the basis and realizes the difference as gain or loss.
+//! Accounts holding xREF before the tracking started have zero cost basis.
+
+use crate::*;
+use crate::utils::U256;
+use std::cmp::min;
+
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Earnings {
+    /// REF paid for the xREF currently held
+    pub cost_basis: Balance,
+    /// gain in REF from unstaked or sent xREF
+    pub realized_gain: Balance,
+    /// loss in REF from unstaked or sent xREF, kept apart from the gain
+    pub realized_loss: Balance,
+}
+
+impl Contract {
+    /// REF value of `shares` xREF at call time.
+    pub(crate) fn internal_share_value(&self, shares: Balance) -> Balance {
+        if self.ft.total_supply == 0 {
+            shares
+        } else {
+            let locked_token_amount =
+                self.locked_token_amount + self.try_distribute_reward(nano_to_sec(env::block_timestamp()));
+            (U256::from(shares) * U256::from(locked_token_amount) / U256::from(self.ft.total_supply)).as_u128()
+        }
+    }
+
+    fn internal_add_cost_basis(&mut self, account_id: &AccountId, value: Balance) {
+        let mut earnings = self.earnings.get(account_id).unwrap_or_default();
+        earnings.cost_basis += value;
+        self.earnings.insert(account_id, &earnings);
+    }
+
+    /// Take the cost basis of `shares` out of the `balance_before` xREF of the account,
+    /// and realize the gain or loss of selling them for `value` REF.
+    fn internal_take_cost_basis(
+        &mut self,
+        account_id: &AccountId,
+        balance_before: Balance,
+        shares: Balance,
+        value: Balance,
+    ) {
+        // a withdraw over the balance panics right after, with the standard error
+        let shares = min(shares, balance_before);
+        let mut earnings = self.earnings.get(account_id).unwrap_or_default();
+        let cost = if balance_before == 0 {
+            0
+        } else {
+            (U256::from(earnings.cost_basis) * U256::from(shares) / U256::from(balance_before)).as_u128()
+        };
+        earnings.cost_basis -= cost;
+        earnings.realized_gain += value.saturating_sub(cost);
+        earnings.realized_loss += cost.saturating_sub(value);
+        self.earnings.insert(account_id, &earnings);
+    }
+
+    pub(crate) fn internal_record_stake(&mut self, account_id: &AccountId, amount: Balance) {
+        self.internal_add_cost_basis(account_id, amount);
+    }
+
+    /// Record the unstake of `shares` for `unlocked` REF, before the shares are withdrawn.
+    pub(crate) fn internal_record_unstake(&mut self, account_id: &AccountId, shares: Balance, unlocked: Balance) {
+        let balance = self.ft.internal_unwrap_balance_of(account_id);
+        self.internal_take_cost_basis(account_id, balance, shares, unlocked);
+    }
+
+    /// A reverted unstake comes back as if bought at the unstake price,
+    /// so its gain stays realized.
+    pub(crate) fn internal_record_revert_unstake(&mut self, account_id: &AccountId, amount: Balance) {
+        self.internal_add_cost_basis(account_id, amount);
+    }
+
+    /// Record the transfer of `shares` at the current price, before the shares are moved.
+    pub(crate) fn internal_record_transfer(&mut self, sender_id: &AccountId, receiver_id: &AccountId, shares: Balance) {
+        let balance = self.ft.internal_unwrap_balance_of(sender_id);
+        let value = self.internal_share_value(shares);
+        self.internal_take_cost_basis(sender_id, balance, shares, value);
+        self.internal_add_cost_basis(receiver_id, value);
+    }
+
+    /// Record the `refund` of a `ft_transfer_call` after it is returned from the receiver,
+    /// `refund_to_sender` is false if the sender has unregistered and the refund is burned.
+    pub(crate) fn internal_record_transfer_refund(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        refund: Balance,
+        refund_to_sender: bool,
+    ) {
+        if !self.ft.accounts.contains_key(receiver_id) {
+            return;
+        }
+        let balance = self.ft.accounts.get(receiver_id).unwrap_or(0) + refund;
+        let value = self.internal_share_value(refund);
+        self.internal_take_cost_basis(receiver_id, balance, refund, value);
+        if refund_to_sender {
+            self.internal_add_cost_basis(sender_id, value);
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Events in the NEP-297 format, for indexers to pick up from the logs.
+
+use near_sdk::log;
+use near_sdk::serde_json::{json, Value};
+
+const EVENT_STANDARD: &str = "xref";
+const EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+/// Log `event` with a single data entry.
+pub(crate) fn emit_event(event: &str, data: Value) {
+    log!(
+        "EVENT_JSON:{}",
+        json!({
+            "standard": EVENT_STANDARD,
+            "version": EVENT_STANDARD_VERSION,
+            "event": event,
+            "data": [data],
+        })
+    );
+}
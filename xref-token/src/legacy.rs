@@ -0,0 +1,17 @@
+//! State layouts of previous releases, kept for `migrate`.
+
+use crate::*;
+
+/// Contract state as deployed in version 1.0.2.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV102 {
+    pub ft: FungibleToken,
+    pub owner_id: AccountId,
+    pub locked_token: AccountId,
+    pub undistributed_reward: Balance,
+    pub locked_token_amount: Balance,
+    pub prev_distribution_time_in_sec: u32,
+    pub reward_genesis_time_in_sec: u32,
+    pub reward_per_sec: Balance,
+    pub account_number: u64,
+}
@@ -3,26 +3,59 @@
 *
 */
 use near_contract_standards::fungible_token::metadata::{
-    FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
+    FungibleTokenMetadata, FungibleTokenMetadataProvider,
 };
+use near_contract_standards::fungible_token::core::FungibleTokenCore;
+use near_contract_standards::fungible_token::resolver::FungibleTokenResolver;
 use near_contract_standards::fungible_token::FungibleToken;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedSet};
 use near_sdk::json_types::{ValidAccountId, U128};
-// Needed by `impl_fungible_token_core` for old Rust.
-#[allow(unused_imports)]
-use near_sdk::{env, near_bindgen, AccountId, Balance, PanicOnDefault, PromiseOrValue};
-use crate::utils::DURATION_30DAYS_IN_SEC;
+use near_sdk::{env, near_bindgen, AccountId, Balance, BorshStorageKey, PanicOnDefault, PromiseOrValue};
+use crate::lock::{Lock, LockSummary};
+use crate::vesting::Vesting;
+use crate::stats::Stats;
+use crate::earnings::Earnings;
+use crate::metadata::default_metadata;
+pub use crate::reward_curve::RewardCurve;
+use crate::utils::{DEFAULT_MIN_RESIDUAL_SUPPLY, DURATION_30DAYS_IN_SEC};
 pub use crate::utils::nano_to_sec;
-pub use crate::views::ContractMetadata;
+pub use crate::views::{
+    ContractMetadata, EarningsInfo, LockInfo, ReconcileInfo, SponsorshipInfo, StatsInfo, VestingInfo,
+};
 
 mod xref;
 mod utils;
 mod owner;
 mod views;
 mod storage_impl;
+mod lock;
+mod vesting;
+mod allowance;
+mod reward_curve;
+mod recovery;
+mod events;
+mod stats;
+mod earnings;
+mod sponsor;
+mod metadata;
+mod legacy;
 
 near_sdk::setup_alloc!();
 
+#[derive(BorshStorageKey, BorshSerialize)]
+pub(crate) enum StorageKey {
+    Locks,
+    LockExpirations,
+    Vestings,
+    Allowances,
+    Holders,
+    Earnings,
+    SponsoredAccounts,
+    Metadata,
+    AllowanceSpenders,
+}
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Contract {
@@ -38,16 +71,61 @@ pub struct Contract {
     /// when would the reward starts to distribute
     pub reward_genesis_time_in_sec: u32,
     pub reward_per_sec: Balance,
+    /// how the undistributed reward is distributed over time
+    pub reward_curve: RewardCurve,
     /// current account number in contract
     pub account_number: u64,
+    /// time-locked xREF of each account
+    pub locks: LookupMap<AccountId, Lock>,
+    /// (amount, amount * unlock_time_in_sec) of locks expiring on each day
+    pub lock_expirations: LookupMap<u32, (Balance, u128)>,
+    /// aggregation of all unexpired locks
+    pub lock_summary: LockSummary,
+    /// owner-created vesting of each beneficiary
+    pub vestings: LookupMap<AccountId, Vesting>,
+    /// xREF each (owner, spender) pair is allowed to transfer
+    pub allowances: LookupMap<(AccountId, AccountId), Balance>,
+    /// the minimum REF amount of a stake
+    pub min_stake_amount: Balance,
+    /// the minimum xREF supply an unstake should leave
+    pub min_residual_supply: Balance,
+    /// owner flagged to wind down the pool
+    pub wind_down: bool,
+    /// cap of locked_token_amount, beyond which stakes are refunded
+    pub max_locked_token_amount: Option<Balance>,
+    /// cap of the REF an account can stake, measured by the account's xREF at current price
+    pub max_account_stake: Option<Balance>,
+    /// unlocked REF on its way out of the contract, not settled by unstake callbacks yet
+    pub unstaking_token_amount: Balance,
+    /// unlocked REF that failed to reach its unregistered or unreachable owner and stays in contract
+    pub orphaned_token_amount: Balance,
+    /// all registered accounts, for enumeration
+    pub holders: UnorderedSet<AccountId>,
+    /// lifetime statistics of the pool
+    pub stats: Stats,
+    /// cost basis and realized gain in REF of each account
+    pub earnings: LookupMap<AccountId, Earnings>,
+    /// owner turned on sponsored registration
+    pub sponsored_registration: bool,
+    /// cap of accounts registered by the sponsor pool at the same time
+    pub max_sponsored_accounts: u64,
+    /// NEAR left to sponsor registrations
+    pub sponsor_pool: Balance,
+    /// the NEAR sponsored to each account for its registration
+    pub sponsored_accounts: LookupMap<AccountId, Balance>,
+    pub sponsored_account_number: u64,
+    pub metadata: LazyOption<FungibleTokenMetadata>,
+    /// spenders each owner has an allowance entry for, to clear them on unregister
+    pub allowance_spenders: LookupMap<AccountId, Vec<AccountId>>,
 }
 
 #[near_bindgen]
 impl Contract {
     #[init]
     pub fn new(owner_id: ValidAccountId, locked_token: ValidAccountId) -> Self {
-        let initial_reward_genisis_time = DURATION_30DAYS_IN_SEC + nano_to_sec(env::block_timestamp());
-        Contract {
+        let cur_time = nano_to_sec(env::block_timestamp());
+        let initial_reward_genisis_time = DURATION_30DAYS_IN_SEC + cur_time;
+        let mut this = Contract {
             ft: FungibleToken::new(b"a".to_vec()),
             owner_id: owner_id.into(),
             locked_token: locked_token.into(),
@@ -56,64 +134,94 @@ impl Contract {
             prev_distribution_time_in_sec: initial_reward_genisis_time,
             reward_genesis_time_in_sec: initial_reward_genisis_time,
             reward_per_sec: 0,
+            reward_curve: RewardCurve::Constant,
             account_number: 0,
-        }
+            locks: LookupMap::new(StorageKey::Locks),
+            lock_expirations: LookupMap::new(StorageKey::LockExpirations),
+            lock_summary: LockSummary::new(cur_time),
+            vestings: LookupMap::new(StorageKey::Vestings),
+            allowances: LookupMap::new(StorageKey::Allowances),
+            min_stake_amount: 0,
+            min_residual_supply: DEFAULT_MIN_RESIDUAL_SUPPLY,
+            wind_down: false,
+            max_locked_token_amount: None,
+            max_account_stake: None,
+            unstaking_token_amount: 0,
+            orphaned_token_amount: 0,
+            holders: UnorderedSet::new(StorageKey::Holders),
+            stats: Stats::default(),
+            earnings: LookupMap::new(StorageKey::Earnings),
+            sponsored_registration: false,
+            max_sponsored_accounts: 0,
+            sponsor_pool: 0,
+            sponsored_accounts: LookupMap::new(StorageKey::SponsoredAccounts),
+            sponsored_account_number: 0,
+            metadata: LazyOption::new(StorageKey::Metadata, Some(&default_metadata())),
+            allowance_spenders: LookupMap::new(StorageKey::AllowanceSpenders),
+        };
+        this.measure_account_storage_usage();
+        this
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenCore for Contract {
+    #[payable]
+    fn ft_transfer(&mut self, receiver_id: ValidAccountId, amount: U128, memo: Option<String>) {
+        let sender_id = env::predecessor_account_id();
+        self.assert_available_balance(&sender_id, amount.into());
+        self.internal_record_transfer(&sender_id, receiver_id.as_ref(), amount.into());
+        self.ft.ft_transfer(receiver_id, amount, memo)
+    }
+
+    #[payable]
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: ValidAccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let sender_id = env::predecessor_account_id();
+        self.assert_available_balance(&sender_id, amount.into());
+        self.internal_record_transfer(&sender_id, receiver_id.as_ref(), amount.into());
+        self.ft.ft_transfer_call(receiver_id, amount, memo, msg)
+    }
+
+    fn ft_total_supply(&self) -> U128 {
+        self.ft.ft_total_supply()
+    }
+
+    fn ft_balance_of(&self, account_id: ValidAccountId) -> U128 {
+        self.ft.ft_balance_of(account_id)
     }
 }
 
-near_contract_standards::impl_fungible_token_core!(Contract, ft);
+#[near_bindgen]
+impl FungibleTokenResolver for Contract {
+    #[private]
+    fn ft_resolve_transfer(
+        &mut self,
+        sender_id: ValidAccountId,
+        receiver_id: ValidAccountId,
+        amount: U128,
+    ) -> U128 {
+        let sender_id: AccountId = sender_id.into();
+        let receiver_account_id: AccountId = receiver_id.as_ref().clone();
+        let (used_amount, burned_amount) = self.ft.internal_ft_resolve_transfer(&sender_id, receiver_id, amount);
+        // the refund is burned if sender has unregistered, and counted as used.
+        if burned_amount > 0 {
+            self.internal_record_transfer_refund(&sender_id, &receiver_account_id, burned_amount, false);
+        } else if used_amount < amount.0 {
+            self.internal_record_transfer_refund(&sender_id, &receiver_account_id, amount.0 - used_amount, true);
+        }
+        used_amount.into()
+    }
+}
 
 #[near_bindgen]
 impl FungibleTokenMetadataProvider for Contract {
     fn ft_metadata(&self) -> FungibleTokenMetadata {
-        let data_url = "data:image/svg+xml;base64,\
-        PHN2ZyB3aWR0aD0iNTYiIGhlaWdodD0iNjIiIHZpZXdCb3g9IjAgMCA1NiA2MiIg\
-        ZmlsbD0ibm9uZSIgeG1sbnM9Imh0dHA6Ly93d3cudzMub3JnLzIwMDAvc3ZnIj4K\
-        PHBhdGggZD0iTTEuODU2OTMgMTcuODg1NkMxLjg1NjkzIDE2LjAyNzggMi44ODY5\
-        MiAxNC4zMjMyIDQuNTMxNTEgMTMuNDU5MkwyNS45MjE2IDIuMjIxNzJDMjcuMzc3\
-        NSAxLjQ1NjgzIDI5LjExNjUgMS40NTY4MyAzMC41NzI0IDIuMjIxNzJMNTEuOTYy\
-        NCAxMy40NTkyQzUzLjYwNyAxNC4zMjMyIDU0LjYzNyAxNi4wMjc4IDU0LjYzNyAx\
-        Ny44ODU2VjQ1LjYzMDRDNTQuNjM3IDQ3LjYwMjEgNTMuNDc4MiA0OS4zODk4IDUx\
-        LjY3ODMgNTAuMTk0N0wzMC4yODgyIDU5Ljc2MDZDMjguOTg5NCA2MC4zNDE0IDI3\
-        LjUwNDYgNjAuMzQxNSAyNi4yMDU3IDU5Ljc2MDZMNC44MTU3IDUwLjE5NDdDMy4w\
-        MTU3NCA0OS4zODk4IDEuODU2OTMgNDcuNjAyMSAxLjg1NjkzIDQ1LjYzMDRMMS44\
-        NTY5MyAxNy44ODU2WiIgZmlsbD0idXJsKCNwYWludDBfbGluZWFyXzEyNDYxXzIw\
-        NzUpIiBzdHJva2U9IiMwMEM2QTIiIHN0cm9rZS13aWR0aD0iMiIvPgo8cGF0aCBk\
-        PSJNMTMuNjk3OCAyMC4zMzJMMjguMjQ3MSAxNEwyOC4yMjAyIDMwLjU0MTdMMjAu\
-        MjgwMyAyMy43MTE2TDEyLjMxMzUgMzAuOTI5NVYyMi4zNjU0QzEyLjMxNTIgMjEu\
-        NDkyMiAxMi44NTM4IDIwLjY5OTMgMTMuNjk3OCAyMC4zMzJaIiBmaWxsPSIjMDBD\
-        NkEyIiBmaWxsLW9wYWNpdHk9IjAuNSIvPgo8cGF0aCBkPSJNMTQuMTAyMyA0Mi43\
-        NjQ1TDI4LjI0NzEgNDYuODYyNkwyOC4yMjAyIDM0LjU5NDRMMjAuMjc5NCAyNy45\
-        NDE0TDEyLjMxMzUgMzQuOTcyMlY0MC41Mjc0QzEyLjMxMzUgNDEuNTUxNSAxMy4w\
-        MzY3IDQyLjQ1NTkgMTQuMTAyMyA0Mi43NjQ1WiIgZmlsbD0iIzAwQzZBMiIgZmls\
-        bC1vcGFjaXR5PSIwLjUiLz4KPHBhdGggZD0iTTQzLjY0NDUgNDIuNzYzM0wyOC4y\
-        NzQ0IDQ2Ljg2MzJMMjguMjQ2OCAzNC41MTU3TDQzLjIyMSAyMi40NjQ4QzQzLjIy\
-        MSAyMi40NjQ4IDQ1Ljc5MyAyNC4zOTk2IDQ1LjAzNzMgMjcuODE5NkM0My43MDQ4\
-        IDMzLjg1MTEgMzUuMTc5NiAzNS45ODY5IDM1LjE3OTYgMzUuOTg2OUw0My45MDk2\
-        IDQxLjE1NjFDNDQuNjE1NSA0MS41NzggNDQuNDU1NCA0Mi41NDcgNDMuNjQ0NSA0\
-        Mi43NjMzWiIgZmlsbD0iIzQ1RkZERSIvPgo8cGF0aCBkPSJNMzguNTkwMyAxOC45\
-        NzkzTDI4LjI3MzQgMTRMMjguMjQ2OCAzMC40MzE1TDQwLjY5NSAyMC4zNTA5QzQw\
-        LjY5NSAyMC4zNTA5IDQwLjQyNzEgMjAuMDU5NyAzOS42OTAxIDE5LjU4MDVDMzku\
-        NDI4OSAxOS40MTE0IDM4LjU5MDMgMTguOTc5MyAzOC41OTAzIDE4Ljk3OTNaIiBm\
-        aWxsPSIjNDVGRkRFIi8+CjxwYXRoIGQ9Ik00MC41NTEgMTYuMDEwMUw0Ni42NjAy\
-        IDE4LjI4MDVDNDYuOTY3NSAxOC4zOTQzIDQ3LjE2OCAxOC42NjU2IDQ3LjE2OCAx\
-        OC45NjVWMjMuMTIwN0M0Ny4xNjggMjMuNDM4OCA0Ni43MjYgMjMuNTgyMiA0Ni41\
-        MDQyIDIzLjMzNDNMNDAuMjU1MyAxNi4zNjg3QzQwLjA4OTMgMTYuMTgzMSA0MC4z\
-        MDYyIDE1LjkxODEgNDAuNTUxIDE2LjAxMDFaIiBmaWxsPSIjNDVGRkRFIi8+Cjxk\
-        ZWZzPgo8bGluZWFyR3JhZGllbnQgaWQ9InBhaW50MF9saW5lYXJfMTI0NjFfMjA3\
-        NSIgeDE9IjI4LjI0NyIgeTE9IjEiIHgyPSIyOC4yNDciIHkyPSI2MC42NzM1IiBn\
-        cmFkaWVudFVuaXRzPSJ1c2VyU3BhY2VPblVzZSI+CjxzdG9wIHN0b3AtY29sb3I9\
-        IiMwMTEzMjAiLz4KPHN0b3Agb2Zmc2V0PSIxIiBzdG9wLWNvbG9yPSIjMDAxMzIw\
-        Ii8+CjwvbGluZWFyR3JhZGllbnQ+CjwvZGVmcz4KPC9zdmc+Cg==";
-
-        FungibleTokenMetadata {
-            spec: FT_METADATA_SPEC.to_string(),
-            name: String::from("xRef Finance Token"),
-            symbol: String::from("xREF"),
-            icon: Some(String::from(data_url)),
-            reference: None,
-            reference_hash: None,
-            decimals: 18,
-        }
+        self.metadata.get().unwrap()
     }
 }
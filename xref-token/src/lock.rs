@@ -0,0 +1,207 @@
+//! Time-locked xREF with a boosted, decaying voting weight (vote-escrow style).
+//!
+//! A lock of `amount` xREF that expires at `unlock_time_in_sec` weighs
+//! `amount * (1 + remaining_time / MAX_LOCK_DURATION_IN_SEC)` at call time,
+//! so a fresh 12 months lock counts double and decays to plain `amount` at expiry,
+//! after which the lock no longer counts at all.
+//! Unlock time is aligned to whole days, so the total weight can be tracked
+//! with one expiration bucket per day.
+
+use crate::*;
+use crate::utils::{settle_storage_deposit, U256, DURATION_1DAY_IN_SEC, DURATION_30DAYS_IN_SEC};
+use near_sdk::log;
+use std::cmp::{max, min};
+
+pub const MIN_LOCK_DURATION_IN_MONTHS: u32 = 1;
+pub const MAX_LOCK_DURATION_IN_MONTHS: u32 = 12;
+pub const MAX_LOCK_DURATION_IN_SEC: u32 = MAX_LOCK_DURATION_IN_MONTHS * DURATION_30DAYS_IN_SEC;
+const MAX_LOCK_DURATION_IN_DAYS: u32 = MAX_LOCK_DURATION_IN_SEC / DURATION_1DAY_IN_SEC;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Lock {
+    /// locked xREF amount
+    pub amount: Balance,
+    /// when the lock expires, aligned to a whole day
+    pub unlock_time_in_sec: u32,
+}
+
+/// Aggregation of all unexpired locks.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Default)]
+pub struct LockSummary {
+    pub amount: Balance,
+    /// sum of `amount * unlock_time_in_sec` over all unexpired locks
+    pub amount_by_unlock_time: u128,
+    /// locks expiring on or before this day have been removed from the summary
+    pub checkpoint_day: u32,
+}
+
+impl LockSummary {
+    pub fn new(cur_time_in_sec: u32) -> Self {
+        Self {
+            amount: 0,
+            amount_by_unlock_time: 0,
+            checkpoint_day: cur_time_in_sec / DURATION_1DAY_IN_SEC,
+        }
+    }
+
+    fn add(&mut self, amount: Balance, amount_by_unlock_time: u128) {
+        self.amount += amount;
+        self.amount_by_unlock_time += amount_by_unlock_time;
+    }
+
+    fn sub(&mut self, amount: Balance, amount_by_unlock_time: u128) {
+        self.amount -= amount;
+        self.amount_by_unlock_time -= amount_by_unlock_time;
+    }
+
+    /// last day whose expiration bucket may still hold locks, when looking at `today`.
+    fn last_expired_day(&self, today: u32) -> u32 {
+        min(today, self.checkpoint_day + MAX_LOCK_DURATION_IN_DAYS)
+    }
+
+    /// sum of boosted weight of all locks in the summary at `cur_time_in_sec`,
+    /// all of them should be unexpired at that time.
+    pub fn voting_weight(&self, cur_time_in_sec: u32) -> Balance {
+        let elapsed = U256::from(self.amount) * U256::from(cur_time_in_sec);
+        let remaining = U256::from(self.amount_by_unlock_time) - min(elapsed, U256::from(self.amount_by_unlock_time));
+        self.amount + (remaining / U256::from(MAX_LOCK_DURATION_IN_SEC)).as_u128()
+    }
+}
+
+impl Lock {
+    pub fn is_active(&self, cur_time_in_sec: u32) -> bool {
+        self.unlock_time_in_sec > cur_time_in_sec
+    }
+
+    fn amount_by_unlock_time(&self) -> u128 {
+        self.amount * self.unlock_time_in_sec as u128
+    }
+
+    fn unlock_day(&self) -> u32 {
+        self.unlock_time_in_sec / DURATION_1DAY_IN_SEC
+    }
+
+    pub fn voting_weight(&self, cur_time_in_sec: u32) -> Balance {
+        if self.is_active(cur_time_in_sec) {
+            let remaining = self.unlock_time_in_sec - cur_time_in_sec;
+            self.amount
+                + (U256::from(self.amount) * U256::from(remaining) / U256::from(MAX_LOCK_DURATION_IN_SEC)).as_u128()
+        } else {
+            0
+        }
+    }
+}
+
+impl Contract {
+    /// xREF of the account that is locked at call time.
+    pub(crate) fn internal_locked_balance(&self, account_id: &AccountId) -> Balance {
+        match self.locks.get(account_id) {
+            Some(lock) if lock.is_active(nano_to_sec(env::block_timestamp())) => lock.amount,
+            _ => 0,
+        }
+    }
+
+    /// Lock summary at `cur_time_in_sec` without touching the state.
+    pub(crate) fn internal_get_lock_summary(&self, cur_time_in_sec: u32) -> LockSummary {
+        let mut summary = self.lock_summary.clone();
+        let today = cur_time_in_sec / DURATION_1DAY_IN_SEC;
+        for day in summary.checkpoint_day + 1..=summary.last_expired_day(today) {
+            if let Some((amount, amount_by_unlock_time)) = self.lock_expirations.get(&day) {
+                summary.sub(amount, amount_by_unlock_time);
+            }
+        }
+        summary.checkpoint_day = max(summary.checkpoint_day, today);
+        summary
+    }
+
+    /// Remove expired locks from the lock summary and clear their expiration buckets.
+    fn internal_checkpoint_locks(&mut self, cur_time_in_sec: u32) {
+        let today = cur_time_in_sec / DURATION_1DAY_IN_SEC;
+        for day in self.lock_summary.checkpoint_day + 1..=self.lock_summary.last_expired_day(today) {
+            if let Some((amount, amount_by_unlock_time)) = self.lock_expirations.remove(&day) {
+                self.lock_summary.sub(amount, amount_by_unlock_time);
+            }
+        }
+        self.lock_summary.checkpoint_day = max(self.lock_summary.checkpoint_day, today);
+    }
+
+    fn internal_add_lock_to_summary(&mut self, lock: &Lock) {
+        let day = lock.unlock_day();
+        let (amount, amount_by_unlock_time) = self.lock_expirations.get(&day).unwrap_or((0, 0));
+        self.lock_expirations.insert(
+            &day,
+            &(amount + lock.amount, amount_by_unlock_time + lock.amount_by_unlock_time()),
+        );
+        self.lock_summary.add(lock.amount, lock.amount_by_unlock_time());
+    }
+
+    fn internal_remove_lock_from_summary(&mut self, lock: &Lock) {
+        let day = lock.unlock_day();
+        let (amount, amount_by_unlock_time) = self.lock_expirations.get(&day).expect("ERR_INTERNAL");
+        if amount == lock.amount {
+            self.lock_expirations.remove(&day);
+        } else {
+            self.lock_expirations.insert(
+                &day,
+                &(amount - lock.amount, amount_by_unlock_time - lock.amount_by_unlock_time()),
+            );
+        }
+        self.lock_summary.sub(lock.amount, lock.amount_by_unlock_time());
+    }
+
+    /// Clear the lock record of an unregistering account.
+    pub(crate) fn internal_remove_lock(&mut self, account_id: &AccountId) {
+        if let Some(lock) = self.locks.remove(account_id) {
+            assert!(!lock.is_active(nano_to_sec(env::block_timestamp())), "ERR_ACCOUNT_HAS_ACTIVE_LOCK");
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Lock `amount` of the predecessor's xREF for `duration_in_months` (1 to 12) months.
+    /// If the account already has an unexpired lock, `amount` is added to it and
+    /// the unlock time is extended to the later one of the two.
+    /// Locked xREF can't be transferred or unstaked until the unlock time.
+    /// Requirements:
+    /// * The predecessor account should be registered.
+    /// * The predecessor account should have at least `amount` of unlocked xREF.
+    /// * Requires attached deposit to cover the storage of the lock, the extra would be refunded.
+    #[payable]
+    pub fn lock(&mut self, amount: U128, duration_in_months: u32) {
+        assert!(env::attached_deposit() > 0, "ERR_REQUIRE_ATTACHED_DEPOSIT");
+        assert!(
+            (MIN_LOCK_DURATION_IN_MONTHS..=MAX_LOCK_DURATION_IN_MONTHS).contains(&duration_in_months),
+            "ERR_INVALID_LOCK_DURATION"
+        );
+        let prev_storage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        let amount: Balance = amount.into();
+        // unlike transfers, nothing is withdrawn here, so the balance must always cover the lock.
+        let balance = self.ft.accounts.get(&account_id).expect("Account not registered.");
+        let restricted = self.internal_locked_balance(&account_id) + self.internal_unvested_balance(&account_id);
+        assert!(balance >= restricted + amount, "ERR_NOT_ENOUGH_UNLOCKED_BALANCE");
+
+        let cur_time = nano_to_sec(env::block_timestamp());
+        self.internal_checkpoint_locks(cur_time);
+
+        let mut lock = Lock {
+            amount,
+            unlock_time_in_sec: (cur_time + duration_in_months * DURATION_30DAYS_IN_SEC) / DURATION_1DAY_IN_SEC
+                * DURATION_1DAY_IN_SEC,
+        };
+        if let Some(prev_lock) = self.locks.get(&account_id) {
+            if prev_lock.is_active(cur_time) {
+                self.internal_remove_lock_from_summary(&prev_lock);
+                lock.amount += prev_lock.amount;
+                lock.unlock_time_in_sec = max(lock.unlock_time_in_sec, prev_lock.unlock_time_in_sec);
+            }
+        }
+        assert!(lock.amount > 0, "ERR_LOCK_ZERO_AMOUNT");
+        self.internal_add_lock_to_summary(&lock);
+        self.locks.insert(&account_id, &lock);
+        log!("{} lock {} token until {}", account_id, lock.amount, lock.unlock_time_in_sec);
+
+        settle_storage_deposit(prev_storage);
+    }
+}
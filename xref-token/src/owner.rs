@@ -1,6 +1,7 @@
 //! Implement all the relevant logic for owner of this contract.
 
 use crate::*;
+use crate::legacy::ContractV102;
 
 #[near_bindgen]
 impl Contract {
@@ -23,6 +24,16 @@ impl Contract {
         self.reward_per_sec = reward_per_sec.into();
     }
 
+    /// Change the emission curve of reward, reward distributed so far would be synced up before the change.
+    pub fn set_reward_curve(&mut self, reward_curve: RewardCurve) {
+        self.assert_owner();
+        let mut reward_curve = reward_curve;
+        reward_curve.assert_valid(nano_to_sec(env::block_timestamp()));
+        reward_curve.prepare();
+        self.distribute_reward();
+        self.reward_curve = reward_curve;
+    }
+
     pub fn reset_reward_genesis_time_in_sec(&mut self, reward_genesis_time_in_sec: u32) {
         self.assert_owner();
         let cur_time = nano_to_sec(env::block_timestamp());
@@ -35,6 +46,44 @@ impl Contract {
         self.prev_distribution_time_in_sec = reward_genesis_time_in_sec;
     }
 
+    /// Set the minimum REF amount of a stake.
+    pub fn set_min_stake_amount(&mut self, min_stake_amount: U128) {
+        self.assert_owner();
+        self.min_stake_amount = min_stake_amount.into();
+    }
+
+    /// Set the stake caps, None means no cap.
+    /// Stakes over the caps are partially accepted and the rest is refunded.
+    pub fn set_stake_caps(&mut self, max_locked_token_amount: Option<U128>, max_account_stake: Option<U128>) {
+        self.assert_owner();
+        self.max_locked_token_amount = max_locked_token_amount.map(|v| v.into());
+        self.max_account_stake = max_account_stake.map(|v| v.into());
+    }
+
+    /// Set the minimum xREF supply an unstake should leave.
+    pub fn set_min_residual_supply(&mut self, min_residual_supply: U128) {
+        self.assert_owner();
+        self.min_residual_supply = min_residual_supply.into();
+    }
+
+    /// Flag or unflag wind-down. In wind-down, nobody can stake,
+    /// and once all reward is distributed, the last holders can unstake everything.
+    pub fn set_wind_down(&mut self, wind_down: bool) {
+        self.assert_owner();
+        self.distribute_reward();
+        self.wind_down = wind_down;
+    }
+
+    /// Add accounts registered before the holder index existed to the index, skip unregistered ones.
+    pub fn backfill_holders(&mut self, account_ids: Vec<ValidAccountId>) {
+        self.assert_owner();
+        for account_id in account_ids {
+            if self.ft.accounts.contains_key(account_id.as_ref()) {
+                self.holders.insert(account_id.as_ref());
+            }
+        }
+    }
+
     pub(crate) fn assert_owner(&self) {
         assert_eq!(
             env::predecessor_account_id(),
@@ -48,8 +97,50 @@ impl Contract {
     #[init(ignore_state)]
     #[private]
     pub fn migrate() -> Self {
-        let prev: Contract = env::state_read().expect("ERR_NOT_INITIALIZED");
-        prev
+        let state = env::storage_read(b"STATE").expect("ERR_NOT_INITIALIZED");
+        if let Ok(contract) = Contract::try_from_slice(&state) {
+            // upgrade to the same state layout, nothing to migrate.
+            return contract;
+        }
+        let prev = ContractV102::try_from_slice(&state).expect("ERR_UNKNOWN_STATE");
+        let cur_time = nano_to_sec(env::block_timestamp());
+        let mut contract = Contract {
+            ft: prev.ft,
+            owner_id: prev.owner_id,
+            locked_token: prev.locked_token,
+            undistributed_reward: prev.undistributed_reward,
+            locked_token_amount: prev.locked_token_amount,
+            prev_distribution_time_in_sec: prev.prev_distribution_time_in_sec,
+            reward_genesis_time_in_sec: prev.reward_genesis_time_in_sec,
+            reward_per_sec: prev.reward_per_sec,
+            reward_curve: RewardCurve::Constant,
+            account_number: prev.account_number,
+            locks: LookupMap::new(StorageKey::Locks),
+            lock_expirations: LookupMap::new(StorageKey::LockExpirations),
+            lock_summary: LockSummary::new(cur_time),
+            vestings: LookupMap::new(StorageKey::Vestings),
+            allowances: LookupMap::new(StorageKey::Allowances),
+            min_stake_amount: 0,
+            min_residual_supply: DEFAULT_MIN_RESIDUAL_SUPPLY,
+            wind_down: false,
+            max_locked_token_amount: None,
+            max_account_stake: None,
+            unstaking_token_amount: 0,
+            orphaned_token_amount: 0,
+            holders: UnorderedSet::new(StorageKey::Holders),
+            stats: Stats::default(),
+            earnings: LookupMap::new(StorageKey::Earnings),
+            sponsored_registration: false,
+            max_sponsored_accounts: 0,
+            sponsor_pool: 0,
+            sponsored_accounts: LookupMap::new(StorageKey::SponsoredAccounts),
+            sponsored_account_number: 0,
+            metadata: LazyOption::new(StorageKey::Metadata, Some(&default_metadata())),
+            allowance_spenders: LookupMap::new(StorageKey::AllowanceSpenders),
+        };
+        // accounts registered before should be added to holders by `backfill_holders`.
+        contract.measure_account_storage_usage();
+        contract
     }
 }
 
@@ -0,0 +1,165 @@
+//! Recovery of token held by this contract but not accounted for in its state,
+//! such as token sent here by a plain `ft_transfer`.
+
+use crate::*;
+use crate::events::emit_event;
+use crate::utils::{
+    ext_self, GAS_FOR_FT_BALANCE_OF, GAS_FOR_FT_TRANSFER, GAS_FOR_RESOLVE_RECONCILE, GAS_FOR_RESOLVE_SWEEP,
+    NO_DEPOSIT,
+};
+use near_contract_standards::fungible_token::core_impl::ext_fungible_token;
+use near_sdk::serde_json::json;
+use near_sdk::{log, serde_json, Promise, PromiseResult};
+use std::cmp::max;
+
+impl Contract {
+    /// REF this contract owes to stakers, to reward, to ongoing and orphaned unstakes.
+    pub(crate) fn internal_accounted_token_amount(&self) -> Balance {
+        self.locked_token_amount + self.undistributed_reward + self.unstaking_token_amount + self.orphaned_token_amount
+    }
+}
+
+/// Parse the balance returned by `ft_balance_of` in a callback.
+fn promise_result_as_balance() -> Balance {
+    assert_eq!(
+        env::promise_results_count(),
+        1,
+        "Err: expected 1 promise result from ft_balance_of"
+    );
+    match env::promise_result(0) {
+        PromiseResult::NotReady => unreachable!(),
+        PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value)
+            .expect("ERR_INVALID_BALANCE")
+            .0,
+        PromiseResult::Failed => env::panic(b"ERR_BALANCE_QUERY_FAILED"),
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Query the balance of this contract on `token_id`, and send it all to `recipient_id`.
+    /// For the locked token, only the surplus above the accounted REF is sent,
+    /// which should be swept when no stake is in flight.
+    /// Only can be called by owner.
+    pub fn sweep_token(&mut self, token_id: ValidAccountId, recipient_id: ValidAccountId) -> Promise {
+        self.assert_owner();
+        assert_ne!(token_id.as_ref(), &env::current_account_id(), "ERR_ILLEGAL_TOKEN");
+        ext_fungible_token::ft_balance_of(
+            env::current_account_id(),
+            token_id.as_ref(),
+            NO_DEPOSIT,
+            GAS_FOR_FT_BALANCE_OF,
+        )
+        .then(ext_self::callback_post_sweep_balance(
+            token_id.into(),
+            recipient_id.into(),
+            U128(self.internal_accounted_token_amount()),
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_SWEEP,
+        ))
+    }
+
+    /// `accounted` is the REF accounted when the balance was queried.
+    /// As unstakes may settle before this callback, the balance could be stale,
+    /// so the larger one of accounted REF then and now is kept.
+    #[private]
+    pub fn callback_post_sweep_balance(&mut self, token_id: AccountId, recipient_id: AccountId, accounted: U128) {
+        let balance = promise_result_as_balance();
+        let amount = if token_id == self.locked_token {
+            balance.saturating_sub(max(accounted.0, self.internal_accounted_token_amount()))
+        } else {
+            balance
+        };
+        if amount == 0 {
+            log!("Nothing to sweep on {}", token_id);
+            return;
+        }
+        log!("Sweep {} of {} to {}", amount, token_id, recipient_id);
+        ext_fungible_token::ft_transfer(
+            recipient_id,
+            U128(amount),
+            Some("sweep".to_string()),
+            &token_id,
+            1,
+            GAS_FOR_FT_TRANSFER,
+        );
+    }
+
+    /// Send `amount` of the orphaned REF, all of it by default, to `recipient_id`,
+    /// such as the account whose unstake was orphaned, once it registers again.
+    /// If the transfer fails, the REF is left as surplus, which can be swept or reconciled.
+    /// Only can be called by owner.
+    pub fn recover_orphaned_token(&mut self, recipient_id: ValidAccountId, amount: Option<U128>) -> Promise {
+        self.assert_owner();
+        let amount = amount.map(|v| v.0).unwrap_or(self.orphaned_token_amount);
+        assert!(amount > 0 && amount <= self.orphaned_token_amount, "ERR_NOT_ENOUGH_ORPHANED_TOKEN");
+        self.orphaned_token_amount -= amount;
+        log!("Recover {} orphaned {} to {}", amount, self.locked_token, recipient_id.as_ref());
+        ext_fungible_token::ft_transfer(
+            recipient_id.into(),
+            U128(amount),
+            Some("recover orphaned".to_string()),
+            &self.locked_token,
+            1,
+            GAS_FOR_FT_TRANSFER,
+        )
+    }
+
+    /// Compare the REF balance of this contract with what it owes, and log the result as a `reconcile` event.
+    /// With `credit_surplus`, the surplus goes into undistributed reward,
+    /// which only owner can ask for, and should do when no stake is in flight.
+    pub fn reconcile(&mut self, credit_surplus: Option<bool>) -> Promise {
+        let credit_surplus = credit_surplus.unwrap_or(false);
+        if credit_surplus {
+            self.assert_owner();
+        }
+        ext_fungible_token::ft_balance_of(
+            env::current_account_id(),
+            &self.locked_token,
+            NO_DEPOSIT,
+            GAS_FOR_FT_BALANCE_OF,
+        )
+        .then(ext_self::callback_post_reconcile_balance(
+            credit_surplus,
+            U128(self.internal_accounted_token_amount()),
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_RECONCILE,
+        ))
+    }
+
+    /// `accounted` is the REF accounted when the balance was queried,
+    /// the larger one of accounted REF then and now is kept as in `callback_post_sweep_balance`.
+    #[private]
+    pub fn callback_post_reconcile_balance(&mut self, credit_surplus: bool, accounted: U128) -> ReconcileInfo {
+        let balance = promise_result_as_balance();
+        let accounted = max(accounted.0, self.internal_accounted_token_amount());
+        let surplus = balance.saturating_sub(accounted);
+        let deficit = accounted.saturating_sub(balance);
+        let credited = if credit_surplus { surplus } else { 0 };
+        if credited > 0 {
+            // Checkpoint
+            self.distribute_reward();
+            let owner_id = self.owner_id.clone();
+            self.internal_add_reward(&owner_id, credited);
+        }
+        emit_event(
+            "reconcile",
+            json!({
+                "balance": U128(balance),
+                "accounted": U128(accounted),
+                "surplus": U128(surplus),
+                "deficit": U128(deficit),
+                "credited": U128(credited),
+            }),
+        );
+        ReconcileInfo {
+            balance: balance.into(),
+            accounted: accounted.into(),
+            surplus: surplus.into(),
+            deficit: deficit.into(),
+            credited: credited.into(),
+        }
+    }
+}
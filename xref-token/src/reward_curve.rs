@@ -0,0 +1,222 @@
+//! Emission curves of reward distribution.
+//!
+//! Each curve tells how much of the undistributed reward at the previous
+//! distribution time should be distributed by now. The decaying curves only
+//! depend on the undistributed reward and the elapsed time, so distributing
+//! in many small steps gives the same result as one big step.
+
+use crate::*;
+use crate::utils::{U256, DURATION_1DAY_IN_SEC};
+use near_sdk::serde::{Deserialize, Serialize};
+use std::cmp::min;
+
+const BPS_DENOMINATOR: u32 = 10_000;
+/// 1.0 in the fixed point numbers used by the exponential decay.
+const FIXED_POINT_ONE: u128 = 1_000_000_000_000_000_000;
+/// precision of a fractional day in the exponential decay, as 2^-40 of a day.
+const FRACTION_BITS: u32 = 40;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+pub enum RewardCurve {
+    /// distribute `reward_per_sec` every second.
+    Constant,
+    /// the distribution rate decays linearly to zero at `end_time_in_sec`,
+    /// when all undistributed reward is out.
+    /// Reward added after that is distributed at `reward_per_sec`.
+    LinearDecay { end_time_in_sec: u32 },
+    /// distribute `daily_rate_bps` of the remaining undistributed reward per day.
+    ExponentialDecay {
+        daily_rate_bps: u32,
+        /// base ^ (2^-k) for k in 1..=FRACTION_BITS, precomputed when the curve is set.
+        #[serde(skip)]
+        fraction_roots: Vec<u128>,
+    },
+}
+
+impl RewardCurve {
+    pub fn assert_valid(&self, cur_time_in_sec: u32) {
+        match self {
+            RewardCurve::Constant => {}
+            RewardCurve::LinearDecay { end_time_in_sec } => {
+                assert!(*end_time_in_sec > cur_time_in_sec, "ERR_END_TIME_IS_PAST_TIME");
+            }
+            RewardCurve::ExponentialDecay { daily_rate_bps, .. } => {
+                assert!(
+                    *daily_rate_bps > 0 && *daily_rate_bps <= BPS_DENOMINATOR,
+                    "ERR_INVALID_DAILY_RATE"
+                );
+            }
+        }
+    }
+
+    /// Precompute what the curve needs for each distribution, call it before the curve takes effect.
+    pub fn prepare(&mut self) {
+        if let RewardCurve::ExponentialDecay { daily_rate_bps, fraction_roots } = self {
+            let one = U256::from(FIXED_POINT_ONE);
+            let mut root = decay_base(*daily_rate_bps);
+            *fraction_roots = (1..=FRACTION_BITS)
+                .map(|_| {
+                    root = (root * one).integer_sqrt();
+                    root.as_u128()
+                })
+                .collect();
+        }
+    }
+
+    /// Return the reward to distribute from `prev_time_in_sec` to `cur_time_in_sec`,
+    /// with `undistributed_reward` left at `prev_time_in_sec`.
+    /// Requires `cur_time_in_sec` > `prev_time_in_sec`.
+    pub fn reward_to_distribute(
+        &self,
+        undistributed_reward: Balance,
+        reward_per_sec: Balance,
+        prev_time_in_sec: u32,
+        cur_time_in_sec: u32,
+    ) -> Balance {
+        let elapsed = cur_time_in_sec - prev_time_in_sec;
+        match self {
+            RewardCurve::Constant => constant_rate(undistributed_reward, reward_per_sec, elapsed),
+            RewardCurve::LinearDecay { end_time_in_sec } => {
+                if prev_time_in_sec >= *end_time_in_sec {
+                    // the curve is over, don't release reward added since then all at once
+                    constant_rate(undistributed_reward, reward_per_sec, elapsed)
+                } else {
+                    // with rate decaying linearly to zero at end time,
+                    // the undistributed reward decays as U(t) = U(prev) * ((end - t) / (end - prev))^2
+                    let remaining_time = U256::from(end_time_in_sec - min(cur_time_in_sec, *end_time_in_sec));
+                    let total_time = U256::from(end_time_in_sec - prev_time_in_sec);
+                    let remaining = U256::from(undistributed_reward) * remaining_time * remaining_time
+                        / (total_time * total_time);
+                    undistributed_reward - remaining.as_u128()
+                }
+            }
+            RewardCurve::ExponentialDecay { daily_rate_bps, fraction_roots } => {
+                // the undistributed reward decays as U(t) = U(prev) * (1 - rate) ^ ((t - prev) / 1day)
+                let one = U256::from(FIXED_POINT_ONE);
+                let factor = pow_fixed(decay_base(*daily_rate_bps), elapsed / DURATION_1DAY_IN_SEC)
+                    * pow_fraction_fixed(fraction_roots, elapsed % DURATION_1DAY_IN_SEC, DURATION_1DAY_IN_SEC)
+                    / one;
+                let remaining = U256::from(undistributed_reward) * factor / one;
+                undistributed_reward - remaining.as_u128()
+            }
+        }
+    }
+}
+
+fn constant_rate(undistributed_reward: Balance, reward_per_sec: Balance, elapsed: u32) -> Balance {
+    let ideal_amount = reward_per_sec * elapsed as u128;
+    min(ideal_amount, undistributed_reward)
+}
+
+/// 1 - daily rate, in fixed point.
+fn decay_base(daily_rate_bps: u32) -> U256 {
+    U256::from(FIXED_POINT_ONE) * U256::from(BPS_DENOMINATOR - daily_rate_bps) / U256::from(BPS_DENOMINATOR)
+}
+
+/// base ^ exp, with base in fixed point.
+fn pow_fixed(mut base: U256, mut exp: u32) -> U256 {
+    let one = U256::from(FIXED_POINT_ONE);
+    let mut result = one;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base / one;
+        }
+        base = base * base / one;
+        exp >>= 1;
+    }
+    result
+}
+
+/// base ^ (numerator / denominator) for numerator < denominator, with base in fixed point,
+/// by multiplying the precomputed base ^ (2^-k) for each bit k of the binary fraction of the exponent.
+fn pow_fraction_fixed(fraction_roots: &[u128], numerator: u32, denominator: u32) -> U256 {
+    assert_eq!(fraction_roots.len(), FRACTION_BITS as usize, "ERR_CURVE_NOT_PREPARED");
+    let one = U256::from(FIXED_POINT_ONE);
+    let bits = ((numerator as u128) << FRACTION_BITS) / denominator as u128;
+    let mut result = one;
+    for (k, root) in (1..=FRACTION_BITS).zip(fraction_roots.iter()) {
+        if bits & (1 << (FRACTION_BITS - k)) != 0 {
+            result = result * U256::from(*root) / one;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u32 = DURATION_1DAY_IN_SEC;
+    const REWARD: Balance = 1_000_000 * FIXED_POINT_ONE;
+
+    fn exponential_decay(daily_rate_bps: u32) -> RewardCurve {
+        let mut curve = RewardCurve::ExponentialDecay { daily_rate_bps, fraction_roots: vec![] };
+        curve.prepare();
+        curve
+    }
+
+    fn assert_close(actual: Balance, expected: f64) {
+        let diff = (actual as f64 - expected).abs();
+        assert!(diff <= REWARD as f64 / 1e12, "actual {}, expected {}", actual, expected);
+    }
+
+    #[test]
+    fn test_constant() {
+        let curve = RewardCurve::Constant;
+        assert_eq!(curve.reward_to_distribute(REWARD, 10, 100, 200), 1000);
+        assert_eq!(curve.reward_to_distribute(500, 10, 100, 200), 500);
+    }
+
+    #[test]
+    fn test_linear_decay() {
+        let end = 1000 + 30 * DAY;
+        let curve = RewardCurve::LinearDecay { end_time_in_sec: end };
+        for &elapsed in [1, 3600, DAY, 7 * DAY + 123, 29 * DAY].iter() {
+            // integral of rate r(t) = 2 * U * (end - t) / (end - start)^2 from start to start + elapsed
+            let total = (end - 1000) as f64;
+            let remaining = (end - 1000 - elapsed) as f64;
+            let expected = REWARD as f64 * (1.0 - (remaining / total).powi(2));
+            assert_close(curve.reward_to_distribute(REWARD, 0, 1000, 1000 + elapsed), expected);
+        }
+        assert_eq!(curve.reward_to_distribute(REWARD, 0, 1000, end), REWARD);
+        assert_eq!(curve.reward_to_distribute(REWARD, 0, 1000, end + 1), REWARD);
+        // reward added after the end is not released at once
+        assert_eq!(curve.reward_to_distribute(REWARD, 10, end, end + 100), 1000);
+        assert_eq!(curve.reward_to_distribute(REWARD, 0, end + 1, end + 100), 0);
+    }
+
+    #[test]
+    fn test_exponential_decay() {
+        let curve = exponential_decay(500);
+        for &elapsed in [1, 3600, DAY / 2, DAY, 7 * DAY + 123, 365 * DAY].iter() {
+            // integral of rate r(t) = -ln(0.95) * U * 0.95 ^ (t / 1day) per day from 0 to elapsed
+            let expected = REWARD as f64 * (1.0 - 0.95f64.powf(elapsed as f64 / DAY as f64));
+            assert_close(curve.reward_to_distribute(REWARD, 0, 1000, 1000 + elapsed), expected);
+        }
+
+        let curve = exponential_decay(BPS_DENOMINATOR);
+        assert_eq!(curve.reward_to_distribute(REWARD, 0, 1000, 1000 + DAY), REWARD);
+    }
+
+    #[test]
+    fn test_decay_in_steps() {
+        for curve in [
+            RewardCurve::LinearDecay { end_time_in_sec: 1000 + 30 * DAY },
+            exponential_decay(500),
+        ]
+        .iter()
+        {
+            let once = curve.reward_to_distribute(REWARD, 0, 1000, 1000 + 10 * DAY);
+            let mut undistributed = REWARD;
+            let mut prev = 1000;
+            for &step in [1, 3599, DAY / 3, 5 * DAY, 10 * DAY - DAY / 3 - 3600 - 5 * DAY].iter() {
+                undistributed -= curve.reward_to_distribute(undistributed, 0, prev, prev + step);
+                prev += step;
+            }
+            assert_eq!(prev, 1000 + 10 * DAY);
+            assert_close(REWARD - undistributed, once as f64);
+        }
+    }
+}
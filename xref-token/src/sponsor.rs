@@ -0,0 +1,71 @@
+//! Sponsored registration: when enabled, an unregistered account staking for the first time
+//! is registered with the registration fee paid from an owner-funded NEAR pool,
+//! up to `max_sponsored_accounts` accounts at the same time.
+//! The fee goes back to the pool when a sponsored account unregisters.
+
+use crate::*;
+use near_contract_standards::storage_management::StorageManagement;
+use near_sdk::{log, Promise};
+
+/// Storage of a record in `sponsored_accounts`: 40 bytes of record overhead,
+/// the key of storage prefix and an account id of max length, and the fee.
+const SPONSORSHIP_RECORD_STORAGE: u64 = 40 + 1 + 4 + 64 + 16;
+
+impl Contract {
+    /// Registration fee of an account plus the storage of its sponsorship record.
+    pub(crate) fn internal_sponsor_cost(&self) -> Balance {
+        self.ft.storage_balance_bounds().min.0 + SPONSORSHIP_RECORD_STORAGE as Balance * env::storage_byte_cost()
+    }
+
+    /// Register the account with the sponsor pool, panics if sponsorship is off or out of budget.
+    pub(crate) fn internal_sponsor_registration(&mut self, account_id: &AccountId) {
+        assert!(self.sponsored_registration, "Account not registered.");
+        assert!(
+            self.sponsored_account_number < self.max_sponsored_accounts,
+            "ERR_SPONSORED_ACCOUNTS_EXCEEDED"
+        );
+        let cost = self.internal_sponsor_cost();
+        assert!(self.sponsor_pool >= cost, "ERR_SPONSOR_POOL_EXHAUSTED");
+        self.sponsor_pool -= cost;
+        self.sponsored_account_number += 1;
+        self.sponsored_accounts.insert(account_id, &cost);
+        self.internal_register_account(account_id);
+        self.account_number += 1;
+        log!("Sponsor registration of {} with {} yoctoNEAR", account_id, cost);
+    }
+
+    /// Return the registration fee of an unregistered sponsored account to the sponsor pool.
+    pub(crate) fn internal_release_sponsorship(&mut self, account_id: &AccountId) {
+        let cost = self.sponsored_accounts.remove(account_id).expect("ERR_INTERNAL");
+        self.sponsor_pool += cost;
+        self.sponsored_account_number -= 1;
+        log!("Sponsored account {} unregistered, {} yoctoNEAR back to pool", account_id, cost);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Add the attached NEAR to the sponsor pool, anyone can fund it.
+    #[payable]
+    pub fn fund_sponsor_pool(&mut self) {
+        self.sponsor_pool += env::attached_deposit();
+    }
+
+    /// Withdraw `amount` of NEAR, or all that is left, from the sponsor pool to owner.
+    /// Only can be called by owner.
+    pub fn withdraw_sponsor_pool(&mut self, amount: Option<U128>) -> Promise {
+        self.assert_owner();
+        let amount = amount.map(|v| v.0).unwrap_or(self.sponsor_pool);
+        assert!(amount <= self.sponsor_pool, "ERR_NOT_ENOUGH_SPONSOR_POOL");
+        self.sponsor_pool -= amount;
+        Promise::new(self.owner_id.clone()).transfer(amount)
+    }
+
+    /// Turn sponsored registration on or off, and cap the number of sponsored accounts.
+    /// Only can be called by owner.
+    pub fn set_sponsored_registration(&mut self, enabled: bool, max_sponsored_accounts: u64) {
+        self.assert_owner();
+        self.sponsored_registration = enabled;
+        self.max_sponsored_accounts = max_sponsored_accounts;
+    }
+}
@@ -0,0 +1,36 @@
+//! Lifetime statistics of the staking pool.
+
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Stats {
+    /// REF ever staked
+    pub total_staked: Balance,
+    /// REF ever unstaked, not counting reverted unstakes
+    pub total_unstaked: Balance,
+    /// REF ever added as reward
+    pub total_reward_deposited: Balance,
+    /// reward ever distributed to locked REF
+    pub total_reward_distributed: Balance,
+    pub stake_count: u64,
+    /// number of unstakes, not counting reverted ones
+    pub unstake_count: u64,
+}
+
+impl Stats {
+    pub fn record_stake(&mut self, amount: Balance) {
+        self.total_staked += amount;
+        self.stake_count += 1;
+    }
+
+    pub fn record_unstake(&mut self, amount: Balance) {
+        self.total_unstaked += amount;
+        self.unstake_count += 1;
+    }
+
+    pub fn revert_unstake(&mut self, amount: Balance) {
+        // the unstake may be sent before the upgrade introducing stats.
+        self.total_unstaked = self.total_unstaked.saturating_sub(amount);
+        self.unstake_count = self.unstake_count.saturating_sub(1);
+    }
+}
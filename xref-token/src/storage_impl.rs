@@ -4,7 +4,47 @@ use near_contract_standards::storage_management::{
 };
 
 use near_sdk::json_types::{ValidAccountId, U128};
-use near_sdk::near_bindgen;
+use near_sdk::{assert_one_yocto, near_bindgen};
+
+impl Contract {
+    /// Add the storage of a holder index entry and an earnings record
+    /// to the storage an account should pay for on registration.
+    pub(crate) fn measure_account_storage_usage(&mut self) {
+        let initial_storage_usage = env::storage_usage();
+        let tmp_account_id = "a".repeat(64);
+        self.holders.insert(&tmp_account_id);
+        self.earnings.insert(&tmp_account_id, &Earnings::default());
+        self.ft.account_storage_usage += env::storage_usage() - initial_storage_usage;
+        self.holders.remove(&tmp_account_id);
+        self.earnings.remove(&tmp_account_id);
+    }
+
+    /// Unregister the predecessor as `FungibleToken::internal_storage_unregister` does,
+    /// which refunds the registration fee to the account,
+    /// except that the fee of a sponsored account goes back to the sponsor pool.
+    fn internal_storage_unregister(&mut self, force: Option<bool>) -> Option<(AccountId, Balance)> {
+        let account_id = env::predecessor_account_id();
+        if !self.sponsored_accounts.contains_key(&account_id) {
+            return self.ft.internal_storage_unregister(force);
+        }
+        assert_one_yocto();
+        let balance = self.ft.accounts.get(&account_id).expect("ERR_INTERNAL");
+        if balance == 0 || force.unwrap_or(false) {
+            self.ft.accounts.remove(&account_id);
+            self.ft.total_supply -= balance;
+            self.internal_release_sponsorship(&account_id);
+            Some((account_id, balance))
+        } else {
+            env::panic(b"Can't unregister the account with the positive balance without force")
+        }
+    }
+
+    /// Register the account in the token and the holder index.
+    pub(crate) fn internal_register_account(&mut self, account_id: &AccountId) {
+        self.ft.internal_register_account(account_id);
+        self.holders.insert(account_id);
+    }
+}
 
 #[near_bindgen]
 impl StorageManagement for Contract {
@@ -16,10 +56,15 @@ impl StorageManagement for Contract {
     ) -> StorageBalance {
         let local_account_id =
             account_id.clone().map(|a| a.into()).unwrap_or_else(|| env::predecessor_account_id());
-        if !self.ft.accounts.contains_key(&local_account_id) {
+        let is_new = !self.ft.accounts.contains_key(&local_account_id);
+        if is_new {
             self.account_number += 1;
         }
-        self.ft.storage_deposit(account_id, registration_only)
+        let storage_balance = self.ft.storage_deposit(account_id, registration_only);
+        if is_new {
+            self.holders.insert(&local_account_id);
+        }
+        storage_balance
     }
 
     #[payable]
@@ -29,14 +74,18 @@ impl StorageManagement for Contract {
 
     #[payable]
     fn storage_unregister(&mut self, force: Option<bool>) -> bool {
-        #[allow(unused_variables)]
-        if let Some((account_id, balance)) = self.ft.internal_storage_unregister(force) {
+        let account_id = env::predecessor_account_id();
+        self.internal_remove_lock(&account_id);
+        self.internal_remove_vesting(&account_id);
+        let unregistered = self.internal_storage_unregister(force).is_some();
+        if unregistered {
             let number = self.account_number.checked_sub(1).unwrap_or(0);
             self.account_number = number;
-            true
-        } else {
-            false
+            self.holders.remove(&account_id);
+            self.earnings.remove(&account_id);
+            self.internal_remove_allowances(&account_id);
         }
+        unregistered
     }
 
     fn storage_balance_bounds(&self) -> StorageBalanceBounds {
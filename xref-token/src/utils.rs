@@ -1,6 +1,5 @@
-
 use near_sdk::json_types::U128;
-use near_sdk::{ext_contract, Gas, Timestamp};
+use near_sdk::{env, ext_contract, Balance, Gas, Promise, StorageUsage, Timestamp};
 use uint::construct_uint;
 
 /// Attach no deposit.
@@ -10,6 +9,24 @@ pub const GAS_FOR_RESOLVE_TRANSFER: Gas = 10_000_000_000_000;
 
 pub const GAS_FOR_FT_TRANSFER: Gas = 20_000_000_000_000;
 
+pub const GAS_FOR_FT_TRANSFER_CALL: Gas = 60_000_000_000_000;
+
+/// Gas for the callback of unstake_call, which may send the refund back to user.
+pub const GAS_FOR_RESOLVE_UNSTAKE_CALL: Gas = 70_000_000_000_000;
+
+pub const GAS_FOR_FT_BALANCE_OF: Gas = 10_000_000_000_000;
+
+/// Gas for the callback of reconcile, which may distribute reward before crediting the surplus.
+pub const GAS_FOR_RESOLVE_RECONCILE: Gas = 30_000_000_000_000;
+
+/// Gas for the callback of sweep_token, which sends the swept token out.
+pub const GAS_FOR_RESOLVE_SWEEP: Gas = 30_000_000_000_000;
+
+/// Keep at least one xREF after an unstake by default.
+pub const DEFAULT_MIN_RESIDUAL_SUPPLY: u128 = 1_000_000_000_000_000_000;
+
+pub const DURATION_1DAY_IN_SEC: u32 = 60 * 60 * 24;
+
 pub const DURATION_30DAYS_IN_SEC: u32 = 60 * 60 * 24 * 30;
 
 
@@ -22,6 +39,22 @@ pub fn nano_to_sec(nano: Timestamp) -> u32 {
     (nano / 1_000_000_000) as u32
 }
 
+/// Charge the storage used since `prev_storage` to the attached deposit,
+/// and refund the rest of the deposit to the predecessor.
+pub(crate) fn settle_storage_deposit(prev_storage: StorageUsage) {
+    let storage_cost = env::storage_usage().saturating_sub(prev_storage) as Balance
+        * env::storage_byte_cost();
+    let attached_deposit = env::attached_deposit();
+    assert!(
+        attached_deposit >= storage_cost,
+        "ERR_INSUFFICIENT_STORAGE_DEPOSIT"
+    );
+    let refund = attached_deposit - storage_cost;
+    if refund > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+    }
+}
+
 
 #[ext_contract(ext_self)]
 pub trait XRef {
@@ -31,6 +64,26 @@ pub trait XRef {
         amount: U128,
         share: U128,
     );
-}
 
+    fn callback_post_unstake_call(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        share: U128,
+    ) -> U128;
+
+    fn callback_post_unstake_refund(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+    );
+
+    fn callback_post_sweep_balance(
+        &mut self,
+        token_id: AccountId,
+        recipient_id: AccountId,
+        accounted: U128,
+    );
 
+    fn callback_post_reconcile_balance(&mut self, credit_surplus: bool, accounted: U128);
+}
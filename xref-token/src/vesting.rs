@@ -0,0 +1,112 @@
+//! Owner-created vesting of xREF, for team and investor allocations.
+//!
+//! Vesting xREF sits in the beneficiary's balance from the beginning,
+//! so the unvested part keeps earning reward through the virtual price,
+//! but only the vested part can be transferred or unstaked.
+
+use crate::*;
+use crate::utils::{settle_storage_deposit, U256};
+use near_sdk::log;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Vesting {
+    pub start_time_in_sec: u32,
+    /// nothing vests before cliff time
+    pub cliff_time_in_sec: u32,
+    pub end_time_in_sec: u32,
+    /// total xREF amount to vest
+    pub total_amount: Balance,
+}
+
+impl Vesting {
+    /// vests linearly from start time to end time, with nothing vested before cliff time
+    pub fn vested_amount(&self, cur_time_in_sec: u32) -> Balance {
+        if cur_time_in_sec < self.cliff_time_in_sec {
+            0
+        } else if cur_time_in_sec >= self.end_time_in_sec {
+            self.total_amount
+        } else {
+            (U256::from(self.total_amount) * U256::from(cur_time_in_sec - self.start_time_in_sec)
+                / U256::from(self.end_time_in_sec - self.start_time_in_sec))
+            .as_u128()
+        }
+    }
+
+    pub fn unvested_amount(&self, cur_time_in_sec: u32) -> Balance {
+        self.total_amount - self.vested_amount(cur_time_in_sec)
+    }
+}
+
+impl Contract {
+    /// xREF of the account that is not vested yet at call time.
+    pub(crate) fn internal_unvested_balance(&self, account_id: &AccountId) -> Balance {
+        self.vestings
+            .get(account_id)
+            .map(|vesting| vesting.unvested_amount(nano_to_sec(env::block_timestamp())))
+            .unwrap_or(0)
+    }
+
+    /// Clear the vesting record of an unregistering account.
+    pub(crate) fn internal_remove_vesting(&mut self, account_id: &AccountId) {
+        if let Some(vesting) = self.vestings.remove(account_id) {
+            assert_eq!(
+                vesting.unvested_amount(nano_to_sec(env::block_timestamp())),
+                0,
+                "ERR_ACCOUNT_HAS_UNVESTED_BALANCE"
+            );
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Move `amount` of xREF from owner to `beneficiary_id` as a vesting.
+    /// Requirements:
+    /// * Only owner can call, and owner should have at least `amount` of unlocked xREF.
+    /// * The beneficiary should be registered, and has no unfinished vesting.
+    /// * start_time_in_sec <= cliff_time_in_sec <= end_time_in_sec, and start_time_in_sec < end_time_in_sec.
+    /// * Requires attached deposit to cover the storage of the vesting, the extra would be refunded.
+    #[payable]
+    pub fn create_vesting(
+        &mut self,
+        beneficiary_id: ValidAccountId,
+        start_time_in_sec: u32,
+        cliff_time_in_sec: u32,
+        end_time_in_sec: u32,
+        amount: U128,
+    ) {
+        self.assert_owner();
+        let prev_storage = env::storage_usage();
+        assert!(
+            start_time_in_sec <= cliff_time_in_sec && cliff_time_in_sec <= end_time_in_sec,
+            "ERR_INVALID_VESTING_TIME"
+        );
+        assert!(start_time_in_sec < end_time_in_sec, "ERR_INVALID_VESTING_TIME");
+        let beneficiary_id: AccountId = beneficiary_id.into();
+        let amount: Balance = amount.into();
+        assert!(amount > 0, "ERR_VESTING_ZERO_AMOUNT");
+        if let Some(vesting) = self.vestings.get(&beneficiary_id) {
+            assert!(
+                vesting.end_time_in_sec <= nano_to_sec(env::block_timestamp()),
+                "ERR_VESTING_EXISTS"
+            );
+        }
+
+        let owner_id = self.owner_id.clone();
+        self.assert_available_balance(&owner_id, amount);
+        self.internal_record_transfer(&owner_id, &beneficiary_id, amount);
+        self.ft.internal_transfer(&owner_id, &beneficiary_id, amount, Some("vesting".to_string()));
+        self.vestings.insert(
+            &beneficiary_id,
+            &Vesting {
+                start_time_in_sec,
+                cliff_time_in_sec,
+                end_time_in_sec,
+                total_amount: amount,
+            },
+        );
+        log!("Create vesting of {} token for {}", amount, beneficiary_id);
+
+        settle_storage_deposit(prev_storage);
+    }
+}
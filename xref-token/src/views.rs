@@ -23,8 +23,112 @@ pub struct ContractMetadata {
     pub prev_distribution_time_in_sec: u32,
     pub reward_genesis_time_in_sec: u32,
     pub reward_per_sec: U128,
+    pub reward_curve: RewardCurve,
     /// current account number in contract
     pub account_number: u64,
+    /// the minimum REF amount of a stake
+    pub min_stake_amount: U128,
+    /// the minimum XREF supply an unstake should leave
+    pub min_residual_supply: U128,
+    /// owner flagged to wind down the pool
+    pub wind_down: bool,
+    /// cap of locked_token_amount
+    pub max_locked_token_amount: Option<U128>,
+    /// cap of the REF an account can stake
+    pub max_account_stake: Option<U128>,
+    /// REF of unstakes whose account unregistered before receiving it
+    pub orphaned_token_amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Deserialize, Debug))]
+pub struct LockInfo {
+    // locked XREF amount
+    pub amount: U128,
+    pub unlock_time_in_sec: u32,
+    // at call time, the boosted voting weight of the lock
+    pub voting_weight: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Deserialize, Debug))]
+pub struct VestingInfo {
+    pub start_time_in_sec: u32,
+    pub cliff_time_in_sec: u32,
+    pub end_time_in_sec: u32,
+    // total XREF amount to vest
+    pub total_amount: U128,
+    // at call time, XREF that can be transferred or unstaked
+    pub vested_amount: U128,
+    // at call time, XREF that still vests
+    pub unvested_amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Deserialize, Debug))]
+pub struct ReconcileInfo {
+    // REF balance of this contract on the locked token
+    pub balance: U128,
+    // REF this contract owes to stakers, to reward, to ongoing and orphaned unstakes
+    pub accounted: U128,
+    // balance above accounted
+    pub surplus: U128,
+    // accounted above balance
+    pub deficit: U128,
+    // the part of surplus credited into undistributed reward
+    pub credited: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Deserialize, Debug))]
+pub struct StatsInfo {
+    // REF ever staked
+    pub total_staked: U128,
+    // REF ever unstaked
+    pub total_unstaked: U128,
+    // REF ever added as reward
+    pub total_reward_deposited: U128,
+    // at call time, reward ever distributed to locked REF
+    pub total_reward_distributed: U128,
+    pub stake_count: u64,
+    pub unstake_count: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Deserialize, Debug))]
+pub struct EarningsInfo {
+    // REF paid for the XREF currently held
+    pub cost_basis: U128,
+    // at call time, REF value of the XREF currently held
+    pub value: U128,
+    // gain in REF from unstaked or sent XREF
+    pub realized_gain: U128,
+    // loss in REF from unstaked or sent XREF
+    pub realized_loss: U128,
+    // at call time, value above cost basis
+    pub unrealized_gain: U128,
+    // at call time, cost basis above value
+    pub unrealized_loss: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Deserialize, Debug))]
+pub struct SponsorshipInfo {
+    pub enabled: bool,
+    // NEAR left to sponsor registrations
+    pub sponsor_pool: U128,
+    // NEAR the pool pays for each sponsored registration
+    pub cost_per_account: U128,
+    pub sponsored_account_number: u64,
+    pub max_sponsored_accounts: u64,
+    // number of registrations the pool can still sponsor
+    pub remaining_accounts: u64,
 }
 
 #[near_bindgen]
@@ -45,7 +149,14 @@ impl Contract {
             prev_distribution_time_in_sec: self.prev_distribution_time_in_sec,
             reward_genesis_time_in_sec: self.reward_genesis_time_in_sec,
             reward_per_sec: self.reward_per_sec.into(),
+            reward_curve: self.reward_curve.clone(),
             account_number: self.account_number,
+            min_stake_amount: self.min_stake_amount.into(),
+            min_residual_supply: self.min_residual_supply.into(),
+            wind_down: self.wind_down,
+            max_locked_token_amount: self.max_locked_token_amount.map(|v| v.into()),
+            max_account_stake: self.max_account_stake.map(|v| v.into()),
+            orphaned_token_amount: self.orphaned_token_amount.into(),
         }
     }
 
@@ -61,4 +172,106 @@ impl Contract {
                 .into()
         }
     }
+
+    /// Return lifetime statistics of the pool.
+    pub fn get_stats(&self) -> StatsInfo {
+        let to_be_distributed = self.try_distribute_reward(nano_to_sec(env::block_timestamp()));
+        StatsInfo {
+            total_staked: self.stats.total_staked.into(),
+            total_unstaked: self.stats.total_unstaked.into(),
+            total_reward_deposited: self.stats.total_reward_deposited.into(),
+            total_reward_distributed: (self.stats.total_reward_distributed + to_be_distributed).into(),
+            stake_count: self.stats.stake_count,
+            unstake_count: self.stats.unstake_count,
+        }
+    }
+
+    /// Return the earnings in REF of given account.
+    pub fn get_earnings(&self, account_id: ValidAccountId) -> EarningsInfo {
+        let earnings = self.earnings.get(account_id.as_ref()).unwrap_or_default();
+        let value = self.internal_share_value(self.ft.accounts.get(account_id.as_ref()).unwrap_or(0));
+        EarningsInfo {
+            cost_basis: earnings.cost_basis.into(),
+            value: value.into(),
+            realized_gain: earnings.realized_gain.into(),
+            realized_loss: earnings.realized_loss.into(),
+            unrealized_gain: value.saturating_sub(earnings.cost_basis).into(),
+            unrealized_loss: earnings.cost_basis.saturating_sub(value).into(),
+        }
+    }
+
+    /// Return the state and remaining budget of sponsored registration.
+    pub fn get_sponsorship(&self) -> SponsorshipInfo {
+        let cost = self.internal_sponsor_cost();
+        let affordable = (self.sponsor_pool / cost) as u64;
+        SponsorshipInfo {
+            enabled: self.sponsored_registration,
+            sponsor_pool: self.sponsor_pool.into(),
+            cost_per_account: cost.into(),
+            sponsored_account_number: self.sponsored_account_number,
+            max_sponsored_accounts: self.max_sponsored_accounts,
+            remaining_accounts: std::cmp::min(
+                affordable,
+                self.max_sponsored_accounts.saturating_sub(self.sponsored_account_number),
+            ),
+        }
+    }
+
+    /// Return the lock of given account, None if the account never locked.
+    pub fn get_lock(&self, account_id: ValidAccountId) -> Option<LockInfo> {
+        let cur_time = nano_to_sec(env::block_timestamp());
+        self.locks.get(account_id.as_ref()).map(|lock| LockInfo {
+            amount: lock.amount.into(),
+            unlock_time_in_sec: lock.unlock_time_in_sec,
+            voting_weight: lock.voting_weight(cur_time).into(),
+        })
+    }
+
+    /// Return the boosted voting weight of given account at call time.
+    pub fn get_voting_weight(&self, account_id: ValidAccountId) -> U128 {
+        let cur_time = nano_to_sec(env::block_timestamp());
+        self.locks
+            .get(account_id.as_ref())
+            .map(|lock| lock.voting_weight(cur_time))
+            .unwrap_or(0)
+            .into()
+    }
+
+    /// Return the sum of boosted voting weight of all accounts at call time.
+    pub fn get_total_voting_weight(&self) -> U128 {
+        let cur_time = nano_to_sec(env::block_timestamp());
+        self.internal_get_lock_summary(cur_time).voting_weight(cur_time).into()
+    }
+
+    /// Return the vesting of given account, None if the account has no vesting.
+    pub fn get_vesting(&self, account_id: ValidAccountId) -> Option<VestingInfo> {
+        let cur_time = nano_to_sec(env::block_timestamp());
+        self.vestings.get(account_id.as_ref()).map(|vesting| VestingInfo {
+            start_time_in_sec: vesting.start_time_in_sec,
+            cliff_time_in_sec: vesting.cliff_time_in_sec,
+            end_time_in_sec: vesting.end_time_in_sec,
+            total_amount: vesting.total_amount.into(),
+            vested_amount: vesting.vested_amount(cur_time).into(),
+            unvested_amount: vesting.unvested_amount(cur_time).into(),
+        })
+    }
+
+    /// Return registered accounts in the holder index, paginated.
+    pub fn get_accounts(&self, from_index: u64, limit: u64) -> Vec<AccountId> {
+        let holders = self.holders.as_vector();
+        (from_index..std::cmp::min(from_index.saturating_add(limit), holders.len()))
+            .map(|index| holders.get(index).unwrap())
+            .collect()
+    }
+
+    /// Return registered accounts in the holder index with their xREF balance, paginated.
+    pub fn get_accounts_with_balances(&self, from_index: u64, limit: u64) -> Vec<(AccountId, U128)> {
+        self.get_accounts(from_index, limit)
+            .into_iter()
+            .map(|account_id| {
+                let balance = self.ft.internal_unwrap_balance_of(&account_id);
+                (account_id, balance.into())
+            })
+            .collect()
+    }
 }
@@ -1,51 +1,252 @@
 
 use crate::*;
-use crate::utils::{ext_self, U256, GAS_FOR_FT_TRANSFER, GAS_FOR_RESOLVE_TRANSFER, NO_DEPOSIT, nano_to_sec};
+use crate::utils::{
+    ext_self, U256, GAS_FOR_FT_TRANSFER, GAS_FOR_FT_TRANSFER_CALL, GAS_FOR_RESOLVE_TRANSFER,
+    GAS_FOR_RESOLVE_UNSTAKE_CALL, NO_DEPOSIT, nano_to_sec,
+};
 use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
 use near_contract_standards::fungible_token::core_impl::ext_fungible_token;
 use near_sdk::json_types::U128;
-use near_sdk::{assert_one_yocto, env, log, Promise, PromiseResult};
+use near_sdk::serde::Deserialize;
+use near_sdk::{assert_one_yocto, env, log, serde_json, Promise, PromiseResult};
 use std::cmp::{max, min};
 
+/// Minimum REF owner should seed for the dead shares.
+pub const MIN_DEAD_SHARES: Balance = 10u128.pow(18);
+
+/// Message parameters to receive via token function call,
+/// other than an empty msg for staking and a non-JSON msg for adding reward.
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde", tag = "action", rename_all = "snake_case")]
+enum TokenReceiverMessage {
+    /// owner seeds the dead shares, which opens the pool to stakes and reward.
+    SeedDeadShares,
+    /// owner stakes for each account the given REF amount, which sum up to the transferred amount.
+    BatchStake { entries: Vec<(ValidAccountId, U128)> },
+}
+
 impl Contract {
-    pub fn internal_stake(&mut self, account_id: &AccountId, amount: Balance) {
-        // check account has registered
-        assert!(self.ft.accounts.contains_key(account_id), "Account not registered.");
-        
+    /// Stake as much of `amount` as the stake caps allow, return the amount not accepted.
+    pub fn internal_stake(&mut self, account_id: &AccountId, amount: Balance) -> Balance {
+        self.assert_seeded();
+        assert!(!self.wind_down, "ERR_WIND_DOWN");
+
+        let accepted = self.internal_stake_allowance(account_id, amount);
+        if accepted == 0 {
+            log!("{} Stake {} assets, exceeds stake cap", account_id, amount);
+            return amount;
+        }
+        assert!(accepted >= self.min_stake_amount, "ERR_STAKE_BELOW_MINIMUM");
+        let refund = amount - accepted;
+        let amount = accepted;
+
+        // check account has registered, or register it with the sponsor pool,
+        // only once some stake is accepted so that a refunded stake takes no sponsorship
+        if !self.ft.accounts.contains_key(account_id) {
+            self.internal_sponsor_registration(account_id);
+        }
+
+        let minted = Self::shares_to_mint(amount, self.ft.total_supply, self.locked_token_amount);
+        self.internal_mint_stake(account_id, amount, minted);
+        refund
+    }
+
+    /// xREF to mint for staking `amount` of REF at the price of `locked_token_amount` / `total_supply`.
+    fn shares_to_mint(amount: Balance, total_supply: Balance, locked_token_amount: Balance) -> Balance {
         let mut minted = amount;
-        if self.ft.total_supply != 0 {
-            assert!(self.locked_token_amount > 0, "ERR_INTERNAL");
-            minted = (U256::from(amount) * U256::from(self.ft.total_supply) / U256::from(self.locked_token_amount)).as_u128();
+        if total_supply != 0 {
+            assert!(locked_token_amount > 0, "ERR_INTERNAL");
+            minted = (U256::from(amount) * U256::from(total_supply) / U256::from(locked_token_amount)).as_u128();
         }
-        
         assert!(minted > 0, "ERR_STAKE_TOO_SMALL");
+        minted
+    }
 
+    fn internal_mint_stake(&mut self, account_id: &AccountId, amount: Balance, minted: Balance) {
         self.locked_token_amount += amount;
         self.ft.internal_deposit(account_id, minted);
+        self.stats.record_stake(amount);
+        self.internal_record_stake(account_id, amount);
         log!("{} Stake {} assets, get {} token", account_id, amount, minted);
     }
 
+    /// Stake for each account of the batch at the same price, return the amount not accepted,
+    /// which includes the entries of unregistered accounts or too small to mint any xREF,
+    /// and the parts over the stake caps.
+    pub(crate) fn internal_batch_stake(
+        &mut self,
+        account_id: &AccountId,
+        amount: Balance,
+        entries: Vec<(ValidAccountId, U128)>,
+    ) -> Balance {
+        assert_eq!(account_id, &self.owner_id, "ERR_NOT_ALLOWED");
+        self.assert_seeded();
+        assert!(!self.wind_down, "ERR_WIND_DOWN");
+        let total = entries.iter().try_fold(0 as Balance, |total, (_, entry_amount)| total.checked_add(entry_amount.0));
+        assert_eq!(total, Some(amount), "ERR_BATCH_AMOUNT_MISMATCH");
+
+        let total_supply = self.ft.total_supply;
+        let locked_token_amount = self.locked_token_amount;
+        assert!(locked_token_amount > 0, "ERR_INTERNAL");
+        let mut refund = 0;
+        for (entry_account_id, entry_amount) in entries {
+            let entry_account_id: AccountId = entry_account_id.into();
+            let entry_amount: Balance = entry_amount.into();
+            if !self.ft.accounts.contains_key(&entry_account_id) {
+                log!("{} not registered, refund {} assets", entry_account_id, entry_amount);
+                refund += entry_amount;
+                continue;
+            }
+            let accepted = self.internal_stake_allowance(&entry_account_id, entry_amount);
+            let minted = (U256::from(accepted) * U256::from(total_supply) / U256::from(locked_token_amount)).as_u128();
+            if minted == 0 {
+                if accepted > 0 {
+                    log!("{} Stake {} assets, too small to mint, refund", entry_account_id, entry_amount);
+                }
+                refund += entry_amount;
+                continue;
+            }
+            refund += entry_amount - accepted;
+            self.internal_mint_stake(&entry_account_id, accepted, minted);
+        }
+        refund
+    }
+
+    /// The part of `amount` the account can stake under the stake caps.
+    fn internal_stake_allowance(&self, account_id: &AccountId, amount: Balance) -> Balance {
+        let mut accepted = amount;
+        if let Some(max_locked_token_amount) = self.max_locked_token_amount {
+            accepted = min(accepted, max_locked_token_amount.saturating_sub(self.locked_token_amount));
+        }
+        if let Some(max_account_stake) = self.max_account_stake {
+            let staked = if self.ft.total_supply == 0 {
+                0
+            } else {
+                (U256::from(self.ft.accounts.get(account_id).unwrap_or(0)) * U256::from(self.locked_token_amount)
+                    / U256::from(self.ft.total_supply))
+                .as_u128()
+            };
+            accepted = min(accepted, max_account_stake.saturating_sub(staked));
+        }
+        accepted
+    }
+
+    /// Stake the very first REF from owner, and mint the xREF to this contract itself.
+    /// Nobody can move these dead shares, so the share price can't be inflated
+    /// by a first staker holding only a few shares.
+    pub(crate) fn internal_seed_dead_shares(&mut self, account_id: &AccountId, amount: Balance) {
+        assert_eq!(account_id, &self.owner_id, "ERR_NOT_ALLOWED");
+        assert_eq!(self.ft.total_supply, 0, "ERR_ALREADY_SEEDED");
+        assert!(amount >= MIN_DEAD_SHARES, "ERR_DEAD_SHARES_TOO_SMALL");
+        let contract_id = env::current_account_id();
+        if !self.ft.accounts.contains_key(&contract_id) {
+            self.internal_register_account(&contract_id);
+        }
+        let minted = Self::shares_to_mint(amount, self.ft.total_supply, self.locked_token_amount);
+        self.internal_mint_stake(&contract_id, amount, minted);
+    }
+
+    /// Stakes and reward are rejected until the dead shares are seeded,
+    /// so nobody can get in first with a few shares and inflate their price.
+    /// Once seeded, the supply never drops to zero again, as the dead shares can't be unstaked.
+    /// Pools migrated with a positive supply count as seeded.
+    pub(crate) fn assert_seeded(&self) {
+        assert!(self.ft.total_supply > 0, "ERR_NOT_SEEDED");
+    }
+
     pub fn internal_add_reward(&mut self, account_id: &AccountId, amount: Balance) {
+        self.assert_seeded();
         self.undistributed_reward += amount;
+        self.stats.total_reward_deposited += amount;
         log!("{} add {} assets as reward", account_id, amount);
     }
 
     /// return the amount of to be distribute reward this time
     pub(crate) fn try_distribute_reward(&self, cur_timestamp_in_sec: u32) -> Balance {
         if cur_timestamp_in_sec > self.reward_genesis_time_in_sec && cur_timestamp_in_sec > self.prev_distribution_time_in_sec {
-            let ideal_amount = self.reward_per_sec * (cur_timestamp_in_sec - self.prev_distribution_time_in_sec) as u128;
-            min(ideal_amount, self.undistributed_reward)
+            self.reward_curve.reward_to_distribute(
+                self.undistributed_reward,
+                self.reward_per_sec,
+                self.prev_distribution_time_in_sec,
+                cur_timestamp_in_sec,
+            )
         } else {
             0
         }
     }
 
+    /// Burn `amount` of xREF from the account, return the unlocked REF amount.
+    fn internal_unstake(&mut self, account_id: &AccountId, amount: Balance) -> Balance {
+        assert!(self.ft.total_supply > 0, "ERR_EMPTY_TOTAL_SUPPLY");
+        self.assert_available_balance(account_id, amount);
+        let unlocked = (U256::from(amount) * U256::from(self.locked_token_amount) / U256::from(self.ft.total_supply)).as_u128();
+
+        self.internal_record_unstake(account_id, amount, unlocked);
+        self.ft.internal_withdraw(account_id, amount);
+        if !self.is_winding_down() {
+            assert!(self.ft.total_supply >= self.min_residual_supply, "ERR_KEEP_AT_LEAST_ONE_XREF");
+        }
+        self.locked_token_amount -= unlocked;
+        self.unstaking_token_amount += unlocked;
+        self.stats.record_unstake(unlocked);
+        unlocked
+    }
+
+    /// Settle `amount` of unlocked REF whose transfer out of the contract has resolved.
+    fn internal_settle_unstaking(&mut self, amount: Balance) {
+        // unstakes sent before the upgrade introducing this counter were never added to it.
+        self.unstaking_token_amount = self.unstaking_token_amount.saturating_sub(amount);
+    }
+
+    /// Revert an unstake whose unlocked token failed to leave the contract.
+    fn internal_revert_unstake(&mut self, sender_id: &AccountId, amount: Balance, share: Balance) {
+        // If account doesn't exit, the unlock token stay in contract.
+        if self.ft.accounts.contains_key(sender_id) {
+            self.locked_token_amount += amount;
+            self.ft.internal_deposit(sender_id, share);
+            self.stats.revert_unstake(amount);
+            self.internal_record_revert_unstake(sender_id, amount);
+            env::log(
+                format!(
+                    "Account {} unstake failed and reverted.",
+                    sender_id
+                )
+                .as_bytes(),
+            );
+        } else {
+            self.orphaned_token_amount += amount;
+            env::log(
+                format!(
+                    "Account {} has unregisterd. unlocking token goes to contract.",
+                    sender_id
+                )
+                .as_bytes(),
+            );
+        }
+    }
+
+    /// In wind-down, the last holders can unstake everything including the residual supply,
+    /// which requires owner has flagged wind-down and all reward has been distributed.
+    pub(crate) fn is_winding_down(&self) -> bool {
+        self.wind_down && self.undistributed_reward == 0
+    }
+
+    /// Panics if `amount` would cut into the locked or unvested xREF of the account.
+    pub(crate) fn assert_available_balance(&self, account_id: &AccountId, amount: Balance) {
+        let restricted = self.internal_locked_balance(account_id) + self.internal_unvested_balance(account_id);
+        if restricted > 0 {
+            let balance = self.ft.internal_unwrap_balance_of(account_id);
+            assert!(balance >= restricted + amount, "ERR_NOT_ENOUGH_UNLOCKED_BALANCE");
+        }
+    }
+
     pub(crate) fn distribute_reward(&mut self) {
         let cur_time = nano_to_sec(env::block_timestamp());
         let new_reward = self.try_distribute_reward(cur_time);
         if new_reward > 0 {
             self.undistributed_reward -= new_reward;
             self.locked_token_amount += new_reward;
+            self.stats.total_reward_distributed += new_reward;
         }
         self.prev_distribution_time_in_sec = max(cur_time, self.reward_genesis_time_in_sec);
     }
@@ -59,7 +260,7 @@ impl Contract {
     /// Requirements:
     /// * The predecessor account should be registered.
     /// * `amount` must be a positive integer.
-    /// * The predecessor account should have at least the `amount` of tokens.
+    /// * The predecessor account should have at least the `amount` of unlocked tokens.
     /// * Requires attached deposit of exactly 1 yoctoNEAR.
     #[payable]
     pub fn unstake(&mut self, amount: U128) -> Promise {
@@ -69,13 +270,7 @@ impl Contract {
         assert_one_yocto();
         let account_id = env::predecessor_account_id();
         let amount: Balance = amount.into();
-
-        assert!(self.ft.total_supply > 0, "ERR_EMPTY_TOTAL_SUPPLY");
-        let unlocked = (U256::from(amount) * U256::from(self.locked_token_amount) / U256::from(self.ft.total_supply)).as_u128();
-
-        self.ft.internal_withdraw(&account_id, amount);
-        assert!(self.ft.total_supply >= 10u128.pow(18), "ERR_KEEP_AT_LEAST_ONE_XREF");
-        self.locked_token_amount -= unlocked;
+        let unlocked = self.internal_unstake(&account_id, amount);
 
         log!("Withdraw {} NEAR from {}", amount, account_id);
 
@@ -97,6 +292,44 @@ impl Contract {
         ))
     }
 
+    /// unstake token and send assets to `receiver_id` with `ft_transfer_call`,
+    /// the part `receiver_id` didn't use would be sent back to the predecessor account.
+    /// Requirements:
+    /// * The predecessor account should be registered.
+    /// * `amount` must be a positive integer.
+    /// * The predecessor account should have at least the `amount` of unlocked tokens.
+    /// * Requires attached deposit of exactly 1 yoctoNEAR.
+    #[payable]
+    pub fn unstake_call(&mut self, amount: U128, receiver_id: ValidAccountId, msg: String) -> Promise {
+        // Checkpoint
+        self.distribute_reward();
+
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let amount: Balance = amount.into();
+        let unlocked = self.internal_unstake(&account_id, amount);
+
+        log!("Withdraw {} NEAR from {} to {}", amount, account_id, receiver_id.as_ref());
+
+        ext_fungible_token::ft_transfer_call(
+            receiver_id.into(),
+            U128(unlocked),
+            None,
+            msg,
+            &self.locked_token,
+            1,
+            GAS_FOR_FT_TRANSFER_CALL,
+        )
+        .then(ext_self::callback_post_unstake_call(
+            account_id.clone(),
+            U128(unlocked),
+            U128(amount),
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_RESOLVE_UNSTAKE_CALL,
+        ))
+    }
+
     #[private]
     pub fn callback_post_unstake(
         &mut self,
@@ -109,31 +342,90 @@ impl Contract {
             1,
             "Err: expected 1 promise result from unstake"
         );
+        self.internal_settle_unstaking(amount.0);
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Successful(_) => {}
             PromiseResult::Failed => {
-                // This reverts the changes from unstake function.
-                // If account doesn't exit, the unlock token stay in contract.
-                if self.ft.accounts.contains_key(&sender_id) {
-                    self.locked_token_amount += amount.0;
-                    self.ft.internal_deposit(&sender_id, share.0);
-                    env::log(
-                        format!(
-                            "Account {} unstake failed and reverted.",
-                            sender_id
-                        )
-                        .as_bytes(),
-                    );
-                } else {
-                    env::log(
-                        format!(
-                            "Account {} has unregisterd. unlocking token goes to contract.",
-                            sender_id
-                        )
-                        .as_bytes(),
-                    );
+                self.internal_revert_unstake(&sender_id, amount.0, share.0);
+            }
+        };
+    }
+
+    /// Return the amount of unlocked token used by the receiver.
+    #[private]
+    pub fn callback_post_unstake_call(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        share: U128,
+    ) -> U128 {
+        assert_eq!(
+            env::promise_results_count(),
+            1,
+            "Err: expected 1 promise result from unstake_call"
+        );
+        self.internal_settle_unstaking(amount.0);
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(value) => {
+                // the receiver's refund has been returned to this contract by ft_resolve_transfer,
+                // pass it on to the sender.
+                let used = serde_json::from_slice::<U128>(&value)
+                    .map(|used| min(used.0, amount.0))
+                    .unwrap_or(amount.0);
+                let refund = amount.0 - used;
+                if refund > 0 {
+                    self.unstaking_token_amount += refund;
+                    ext_fungible_token::ft_transfer(
+                        sender_id.clone(),
+                        U128(refund),
+                        None,
+                        &self.locked_token,
+                        1,
+                        GAS_FOR_FT_TRANSFER,
+                    )
+                    .then(ext_self::callback_post_unstake_refund(
+                        sender_id,
+                        U128(refund),
+                        &env::current_account_id(),
+                        NO_DEPOSIT,
+                        GAS_FOR_RESOLVE_TRANSFER,
+                    ));
                 }
+                used.into()
+            }
+            PromiseResult::Failed => {
+                self.internal_revert_unstake(&sender_id, amount.0, share.0);
+                U128(0)
+            }
+        }
+    }
+
+    #[private]
+    pub fn callback_post_unstake_refund(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+    ) {
+        assert_eq!(
+            env::promise_results_count(),
+            1,
+            "Err: expected 1 promise result from unstake refund"
+        );
+        self.internal_settle_unstaking(amount.0);
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                self.orphaned_token_amount += amount.0;
+                env::log(
+                    format!(
+                        "Account {} failed to receive unstake refund {}. refund token goes to contract.",
+                        sender_id, amount.0
+                    )
+                    .as_bytes(),
+                );
             }
         };
     }
@@ -155,8 +447,19 @@ impl FungibleTokenReceiver for Contract {
         assert_eq!(token_in, self.locked_token, "ERR_ILLEGAL_TOKEN");
         if msg.is_empty() {
             // user stake.
-            self.internal_stake(sender_id.as_ref(), amount);
-            PromiseOrValue::Value(U128(0))
+            let refund = self.internal_stake(sender_id.as_ref(), amount);
+            PromiseOrValue::Value(U128(refund))
+        } else if let Ok(message) = serde_json::from_str::<TokenReceiverMessage>(&msg) {
+            match message {
+                TokenReceiverMessage::SeedDeadShares => {
+                    self.internal_seed_dead_shares(sender_id.as_ref(), amount);
+                    PromiseOrValue::Value(U128(0))
+                }
+                TokenReceiverMessage::BatchStake { entries } => {
+                    let refund = self.internal_batch_stake(sender_id.as_ref(), amount, entries);
+                    PromiseOrValue::Value(U128(refund))
+                }
+            }
         } else {
             // deposit reward
             log!("Add reward {} token with msg {}", amount, msg);
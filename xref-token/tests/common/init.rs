@@ -7,7 +7,29 @@ near_sdk_sim::lazy_static_include::lazy_static_include_bytes! {
     XREF_WASM_BYTES => "../res/xref_token_release.wasm",
 }
 
+/// REF owner seeds as dead shares in the test environment.
+pub const DEAD_SHARES: u128 = 1_000_000_000_000_000_000;
+
+pub const SEED_MSG: &str = "{\"action\": \"seed_dead_shares\"}";
+
+/// Owner seeds the dead shares, which opens the pool to stakes and reward.
+pub fn seed_dead_shares(owner: &UserAccount, ref_contract: &ContractAccount<TestToken>, xref_contract: &ContractAccount<XRefToken>) {
+    call!(
+        owner,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), DEAD_SHARES.into(), None, SEED_MSG.to_string()),
+        deposit = 1
+    )
+    .assert_success();
+}
+
+/// Environment with the pool seeded by owner.
 pub fn init_env(register_user: bool) -> (UserAccount, UserAccount, UserAccount, ContractAccount<TestToken>, ContractAccount<XRefToken>){
+    let (root, owner, user, ref_contract, xref_contract) = init_env_unseeded(register_user);
+    seed_dead_shares(&owner, &ref_contract, &xref_contract);
+    (root, owner, user, ref_contract, xref_contract)
+}
+
+pub fn init_env_unseeded(register_user: bool) -> (UserAccount, UserAccount, UserAccount, ContractAccount<TestToken>, ContractAccount<XRefToken>){
     let root = init_simulator(None);
 
     let owner = root.create_user("owner".to_string(), to_yocto("100"));
@@ -38,4 +60,33 @@ pub fn init_env(register_user: bool) -> (UserAccount, UserAccount, UserAccount,
         call!(user, xref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
     }
     (root, owner, user, ref_contract, xref_contract)
-}
\ No newline at end of file
+}
+
+/// Deploy another xref contract on top of the same locked token, register it on the locked token, and seed it.
+#[allow(dead_code)]
+pub fn deploy_xref(root: &UserAccount, contract_id: &str, owner: &UserAccount, ref_contract: &ContractAccount<TestToken>) -> ContractAccount<XRefToken> {
+    let xref_contract = deploy!(
+        contract: XRefToken,
+        contract_id: contract_id.to_string(),
+        bytes: &XREF_WASM_BYTES,
+        signer_account: root
+    );
+    call!(root, xref_contract.new(owner.valid_account_id(), ref_contract.valid_account_id())).assert_success();
+    call!(root, ref_contract.storage_deposit(Some(xref_contract.valid_account_id()), None), deposit = to_yocto("1")).assert_success();
+    seed_dead_shares(owner, ref_contract, &xref_contract);
+    xref_contract
+}
+
+/// Deploy another test token, and register the xref contract on it.
+#[allow(dead_code)]
+pub fn deploy_token(root: &UserAccount, contract_id: &str, xref_contract: &ContractAccount<XRefToken>) -> ContractAccount<TestToken> {
+    let token_contract = deploy!(
+        contract: TestToken,
+        contract_id: contract_id.to_string(),
+        bytes: &TEST_WASM_BYTES,
+        signer_account: root
+    );
+    call!(root, token_contract.new(contract_id.to_string(), contract_id.to_string(), 18)).assert_success();
+    call!(root, token_contract.storage_deposit(Some(xref_contract.valid_account_id()), None), deposit = to_yocto("1")).assert_success();
+    token_contract
+}
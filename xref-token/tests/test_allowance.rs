@@ -0,0 +1,145 @@
+use near_sdk_sim::{call, view, to_yocto};
+use near_sdk::json_types::U128;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+#[test]
+fn test_allowance_storage(){
+    let (root, _, user, ref_contract, xref_contract) =
+        init_env(true);
+    let spender = root.create_user("spender".to_string(), to_yocto("100"));
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+
+    let out_come = call!(
+        user,
+        xref_contract.ft_approve(spender.valid_account_id(), to_yocto("5").into()),
+        deposit = 1
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_INSUFFICIENT_STORAGE_DEPOSIT"));
+
+    // the storage of allowance is charged from the deposit and the rest is refunded
+    let xref_account = xref_contract.user_account.account().unwrap();
+    let user_balance = user.account().unwrap().amount;
+    call!(
+        user,
+        xref_contract.ft_approve(spender.valid_account_id(), to_yocto("5").into()),
+        deposit = to_yocto("1")
+    )
+    .assert_success();
+    let xref_account_approved = xref_contract.user_account.account().unwrap();
+    let storage_cost = (xref_account_approved.storage_usage - xref_account.storage_usage) as u128 * 10u128.pow(19);
+    assert!(storage_cost > 0);
+    assert!(xref_account_approved.amount - xref_account.amount >= storage_cost);
+    let user_balance_approved = user.account().unwrap().amount;
+    assert!(user_balance - user_balance_approved >= storage_cost);
+    assert!(user_balance - user_balance_approved < to_yocto("0.1"));
+    assert_eq!(view!(xref_contract.ft_allowance(user.valid_account_id(), spender.valid_account_id())).unwrap_json::<U128>().0, to_yocto("5"));
+
+    // update an allowance costs no more storage
+    call!(
+        user,
+        xref_contract.ft_approve(spender.valid_account_id(), to_yocto("6").into()),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(xref_contract.user_account.account().unwrap().storage_usage, xref_account_approved.storage_usage);
+
+    // revoke pays back the storage
+    call!(
+        user,
+        xref_contract.ft_approve(spender.valid_account_id(), U128(0)),
+        deposit = 1
+    )
+    .assert_success();
+    let xref_account_revoked = xref_contract.user_account.account().unwrap();
+    assert_eq!(xref_account_revoked.storage_usage, xref_account.storage_usage);
+    assert_eq!(view!(xref_contract.ft_allowance(user.valid_account_id(), spender.valid_account_id())).unwrap_json::<U128>().0, 0);
+}
+
+#[test]
+fn test_transfer_from(){
+    let (root, _, user, ref_contract, xref_contract) =
+        init_env(true);
+    let spender = root.create_user("spender".to_string(), to_yocto("100"));
+    call!(spender, xref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        user,
+        xref_contract.ft_approve(spender.valid_account_id(), to_yocto("5").into()),
+        deposit = to_yocto("0.1")
+    )
+    .assert_success();
+
+    call!(
+        spender,
+        xref_contract.ft_transfer_from(user.valid_account_id(), spender.valid_account_id(), to_yocto("3").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(to_yocto("7"), view!(xref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+    assert_eq!(to_yocto("3"), view!(xref_contract.ft_balance_of(spender.valid_account_id())).unwrap_json::<U128>().0);
+    assert_eq!(view!(xref_contract.ft_allowance(user.valid_account_id(), spender.valid_account_id())).unwrap_json::<U128>().0, to_yocto("2"));
+
+    let out_come = call!(
+        spender,
+        xref_contract.ft_transfer_from(user.valid_account_id(), spender.valid_account_id(), to_yocto("3").into(), None),
+        deposit = 1
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_ALLOWANCE_EXCEEDED"));
+
+    // allowance can't touch locked xREF
+    call!(
+        user,
+        xref_contract.lock(to_yocto("6").into(), 1),
+        deposit = to_yocto("0.1")
+    )
+    .assert_success();
+    let out_come = call!(
+        spender,
+        xref_contract.ft_transfer_from(user.valid_account_id(), spender.valid_account_id(), to_yocto("2").into(), None),
+        deposit = 1
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ENOUGH_UNLOCKED_BALANCE"));
+}
+
+#[test]
+fn test_allowance_unregister(){
+    let (root, _, _, _, xref_contract) =
+        init_env(false);
+    let holder = root.create_user("holder".to_string(), to_yocto("100"));
+    let spender = root.create_user("spender".to_string(), to_yocto("100"));
+    call!(holder, xref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+
+    let xref_account = xref_contract.user_account.account().unwrap();
+    call!(
+        holder,
+        xref_contract.ft_approve(spender.valid_account_id(), to_yocto("5").into()),
+        deposit = to_yocto("0.1")
+    )
+    .assert_success();
+    assert!(xref_contract.user_account.account().unwrap().storage_usage > xref_account.storage_usage);
+
+    // unregister clears the allowances of the holder with their storage
+    call!(holder, xref_contract.storage_unregister(None), deposit = 1).assert_success();
+    assert!(xref_contract.user_account.account().unwrap().storage_usage < xref_account.storage_usage);
+    assert_eq!(view!(xref_contract.ft_allowance(holder.valid_account_id(), spender.valid_account_id())).unwrap_json::<U128>().0, 0);
+}
@@ -0,0 +1,104 @@
+use near_sdk_sim::{call, view, to_yocto};
+use xref_token::EarningsInfo;
+use near_sdk::json_types::U128;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+#[test]
+fn test_earnings(){
+    let (root, owner, user, ref_contract, xref_contract) =
+        init_env(true);
+    let user2 = root.create_user("user2".to_string(), to_yocto("100"));
+    call!(user2, xref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    let earnings = view!(xref_contract.get_earnings(user.valid_account_id())).unwrap_json::<EarningsInfo>();
+    assert_eq!(earnings.cost_basis.0, to_yocto("10"));
+    assert_eq!(earnings.value.0, to_yocto("10"));
+    assert_eq!(earnings.realized_gain.0, 0);
+    assert_eq!(earnings.unrealized_gain.0, 0);
+
+    // double the price by distributing as much reward as locked, which includes the dead shares
+    call!(
+        owner,
+        xref_contract.modify_reward_per_sec(to_yocto("1").into(), true)
+    )
+    .assert_success();
+    let current_timestamp = root.borrow_runtime().cur_block.block_timestamp;
+    call!(
+        owner,
+        xref_contract.reset_reward_genesis_time_in_sec(nano_to_sec(current_timestamp) + 10)
+    ).assert_success();
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), U128(to_yocto("10") + DEAD_SHARES), None, "reward".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    root.borrow_runtime_mut().cur_block.block_timestamp = current_timestamp + 100 * 1_000_000_000;
+
+    let earnings = view!(xref_contract.get_earnings(user.valid_account_id())).unwrap_json::<EarningsInfo>();
+    assert_eq!(earnings.value.0, to_yocto("20"));
+    assert_eq!(earnings.unrealized_gain.0, to_yocto("10"));
+
+    // sending XREF realizes the gain at current price, and receiver gets the basis of current value
+    call!(
+        user,
+        xref_contract.ft_transfer(user2.valid_account_id(), to_yocto("5").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    let earnings = view!(xref_contract.get_earnings(user.valid_account_id())).unwrap_json::<EarningsInfo>();
+    assert_eq!(earnings.cost_basis.0, to_yocto("5"));
+    assert_eq!(earnings.realized_gain.0, to_yocto("5"));
+    assert_eq!(earnings.unrealized_gain.0, to_yocto("5"));
+    let earnings = view!(xref_contract.get_earnings(user2.valid_account_id())).unwrap_json::<EarningsInfo>();
+    assert_eq!(earnings.cost_basis.0, to_yocto("10"));
+    assert_eq!(earnings.realized_gain.0, 0);
+    assert_eq!(earnings.unrealized_gain.0, 0);
+
+    // so does unstaking
+    call!(
+        user,
+        xref_contract.unstake(to_yocto("5").into()),
+        deposit = 1
+    )
+    .assert_success();
+    let earnings = view!(xref_contract.get_earnings(user.valid_account_id())).unwrap_json::<EarningsInfo>();
+    assert_eq!(earnings.cost_basis.0, 0);
+    assert_eq!(earnings.value.0, 0);
+    assert_eq!(earnings.realized_gain.0, to_yocto("10"));
+    assert_eq!(earnings.unrealized_gain.0, 0);
+
+    // a loss is realized apart from the gain, 3 yocto REF only buys 1 yocto XREF worth 2 yocto REF
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), U128(3), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    let earnings = view!(xref_contract.get_earnings(user.valid_account_id())).unwrap_json::<EarningsInfo>();
+    assert_eq!(earnings.cost_basis.0, 3);
+    assert_eq!(earnings.value.0, 2);
+    assert_eq!(earnings.unrealized_loss.0, 1);
+    call!(
+        user,
+        xref_contract.unstake(U128(1)),
+        deposit = 1
+    )
+    .assert_success();
+    let earnings = view!(xref_contract.get_earnings(user.valid_account_id())).unwrap_json::<EarningsInfo>();
+    assert_eq!(earnings.cost_basis.0, 0);
+    assert_eq!(earnings.realized_gain.0, to_yocto("10"));
+    assert_eq!(earnings.realized_loss.0, 1);
+    assert_eq!(earnings.unrealized_loss.0, 0);
+}
@@ -0,0 +1,139 @@
+use near_sdk_sim::{call, view, to_yocto, ContractAccount, UserAccount};
+use test_token::ContractContract as TestToken;
+use xref_token::{ContractContract as XRefToken, ContractMetadata};
+use near_sdk::json_types::U128;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+/// Start a fast reward distribution, and prepare a victim with 99 REF
+fn prepare(root: &UserAccount, owner: &UserAccount, ref_contract: &ContractAccount<TestToken>, xref_contract: &ContractAccount<XRefToken>) -> UserAccount {
+    call!(
+        owner,
+        xref_contract.modify_reward_per_sec(to_yocto("100").into(), true)
+    )
+    .assert_success();
+    let current_timestamp = root.borrow_runtime().cur_block.block_timestamp;
+    call!(
+        owner,
+        xref_contract.reset_reward_genesis_time_in_sec(nano_to_sec(current_timestamp) + 10)
+    ).assert_success();
+
+    let victim = root.create_user("victim".to_string(), to_yocto("100"));
+    call!(victim, ref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+    call!(root, ref_contract.mint(victim.valid_account_id(), to_yocto("99").into())).assert_success();
+    call!(victim, xref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+    victim
+}
+
+/// Attacker stakes `stake_amount` first, then donates 50 REF as reward and waits for it to be distributed,
+/// return the REF value of victim's XREF after victim stakes 99 REF.
+fn attack(root: &UserAccount, attacker: &UserAccount, victim: &UserAccount, ref_contract: &ContractAccount<TestToken>, xref_contract: &ContractAccount<XRefToken>, stake_amount: u128) -> u128 {
+    call!(
+        attacker,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), U128(stake_amount), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        attacker,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("50").into(), None, "reward".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    let current_timestamp = root.borrow_runtime().cur_block.block_timestamp;
+    root.borrow_runtime_mut().cur_block.block_timestamp = current_timestamp + 100 * 1_000_000_000;
+
+    call!(
+        victim,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("99").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    let xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_eq!(xref_info.cur_undistributed_reward.0, 0);
+    let victim_shares = view!(xref_contract.ft_balance_of(victim.valid_account_id())).unwrap_json::<U128>().0;
+    (U256::from(victim_shares) * U256::from(xref_info.cur_locked_token_amount.0) / U256::from(xref_info.supply.0)).as_u128()
+}
+
+#[test]
+fn test_front_run_before_seed(){
+    let (root, owner, user, ref_contract, xref_contract) =
+        init_env_unseeded(true);
+    let victim = prepare(&root, &owner, &ref_contract, &xref_contract);
+
+    // attacker can't get in before owner seeds, neither by staking nor by donating reward
+    let out_come = call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), U128(1), None, "".to_string()),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_SEEDED"));
+    let out_come = call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("50").into(), None, "reward".to_string()),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_SEEDED"));
+    assert_eq!(view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0, to_yocto("100"));
+    let xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_xref(&xref_info, 0, 0, 0);
+
+    // so seeding still goes through
+    seed_dead_shares(&owner, &ref_contract, &xref_contract);
+    assert_eq!(view!(xref_contract.ft_balance_of(xref_contract.valid_account_id())).unwrap_json::<U128>().0, DEAD_SHARES);
+
+    // and the cheapest attack after seeding only costs victim the rounding
+    let victim_value = attack(&root, &user, &victim, &ref_contract, &xref_contract, 1);
+    assert!(victim_value <= to_yocto("99"));
+    assert!(victim_value > to_yocto("99") - to_yocto("0.000001"));
+}
+
+#[test]
+fn test_inflation_attack_with_dead_shares(){
+    let (root, owner, user, ref_contract, xref_contract) =
+        init_env_unseeded(true);
+    let victim = prepare(&root, &owner, &ref_contract, &xref_contract);
+
+    // only owner can seed
+    let out_come = call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("1").into(), None, SEED_MSG.to_string()),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+
+    call!(
+        owner,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("1").into(), None, SEED_MSG.to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(view!(xref_contract.ft_balance_of(xref_contract.valid_account_id())).unwrap_json::<U128>().0, to_yocto("1"));
+    let out_come = call!(
+        owner,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("1").into(), None, SEED_MSG.to_string()),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_ALREADY_SEEDED"));
+
+    call!(
+        owner,
+        xref_contract.set_min_stake_amount(to_yocto("1").into())
+    )
+    .assert_success();
+    let out_come = call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), U128(1), None, "".to_string()),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_STAKE_BELOW_MINIMUM"));
+
+    // the cheapest attack now only costs victim the rounding
+    let victim_value = attack(&root, &user, &victim, &ref_contract, &xref_contract, to_yocto("1"));
+    assert!(victim_value <= to_yocto("99"));
+    assert!(victim_value > to_yocto("99") - 100);
+}
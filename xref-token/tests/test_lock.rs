@@ -0,0 +1,163 @@
+use near_sdk_sim::{call, view, to_yocto};
+use xref_token::LockInfo;
+use near_sdk::json_types::U128;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+const DAY_IN_SEC: u32 = 24 * 60 * 60;
+const MAX_LOCK_DURATION_IN_SEC: u32 = 12 * 30 * DAY_IN_SEC;
+
+#[test]
+fn test_lock(){
+    let (root, _, user, ref_contract, xref_contract) =
+        init_env(true);
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+
+    let current_timestamp = root.borrow_runtime().cur_block.block_timestamp;
+    call!(
+        user,
+        xref_contract.lock(to_yocto("6").into(), 12),
+        deposit = to_yocto("0.1")
+    )
+    .assert_success();
+
+    let lock_info = view!(xref_contract.get_lock(user.valid_account_id())).unwrap_json::<Option<LockInfo>>().unwrap();
+    let unlock_time = (nano_to_sec(current_timestamp) + MAX_LOCK_DURATION_IN_SEC) / DAY_IN_SEC * DAY_IN_SEC;
+    assert_eq!(lock_info.amount.0, to_yocto("6"));
+    assert_eq!(lock_info.unlock_time_in_sec, unlock_time);
+    // the weight decays from the time of the view, a few blocks after the lock
+    let remaining = (unlock_time - nano_to_sec(root.borrow_runtime().cur_block.block_timestamp)) as u128;
+    let weight = to_yocto("6") + to_yocto("6") * remaining / MAX_LOCK_DURATION_IN_SEC as u128;
+    assert_eq!(lock_info.voting_weight.0, weight);
+    assert_eq!(view!(xref_contract.get_voting_weight(user.valid_account_id())).unwrap_json::<U128>().0, weight);
+    assert_eq!(view!(xref_contract.get_total_voting_weight()).unwrap_json::<U128>().0, weight);
+
+    // locked xREF can't be transferred or unstaked
+    let out_come = call!(
+        user,
+        xref_contract.unstake(to_yocto("5").into()),
+        deposit = 1
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ENOUGH_UNLOCKED_BALANCE"));
+
+    let user2 = root.create_user("user2".to_string(), to_yocto("100"));
+    call!(user2, xref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+    let out_come = call!(
+        user,
+        xref_contract.ft_transfer(user2.valid_account_id(), to_yocto("5").into(), None),
+        deposit = 1
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ENOUGH_UNLOCKED_BALANCE"));
+
+    // the unlocked part is still free
+    call!(
+        user,
+        xref_contract.ft_transfer(user2.valid_account_id(), to_yocto("2").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        user,
+        xref_contract.unstake(to_yocto("1").into()),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(to_yocto("91"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+
+    // weight decays as time goes
+    root.borrow_runtime_mut().cur_block.block_timestamp = (unlock_time - MAX_LOCK_DURATION_IN_SEC / 2) as u64 * 1_000_000_000;
+    let weight = to_yocto("6") + to_yocto("3");
+    assert_eq!(view!(xref_contract.get_voting_weight(user.valid_account_id())).unwrap_json::<U128>().0, weight);
+    assert_eq!(view!(xref_contract.get_total_voting_weight()).unwrap_json::<U128>().0, weight);
+
+    // and the lock is over after unlock time
+    root.borrow_runtime_mut().cur_block.block_timestamp = unlock_time as u64 * 1_000_000_000;
+    assert_eq!(view!(xref_contract.get_voting_weight(user.valid_account_id())).unwrap_json::<U128>().0, 0);
+    assert_eq!(view!(xref_contract.get_total_voting_weight()).unwrap_json::<U128>().0, 0);
+    call!(
+        user,
+        xref_contract.unstake(to_yocto("6").into()),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(to_yocto("97"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+}
+
+#[test]
+fn test_lock_extend(){
+    let (root, _, user, ref_contract, xref_contract) =
+        init_env(true);
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+
+    let out_come = call!(
+        user,
+        xref_contract.lock(to_yocto("1").into(), 13),
+        deposit = to_yocto("0.1")
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_INVALID_LOCK_DURATION"));
+
+    let out_come = call!(
+        user,
+        xref_contract.lock(to_yocto("11").into(), 1),
+        deposit = to_yocto("0.1")
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ENOUGH_UNLOCKED_BALANCE"));
+
+    // an unregistered account holds nothing to lock
+    let stranger = root.create_user("stranger".to_string(), to_yocto("100"));
+    let out_come = call!(
+        stranger,
+        xref_contract.lock(to_yocto("1").into(), 1),
+        deposit = to_yocto("0.1")
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("Account not registered."));
+    assert_eq!(view!(xref_contract.get_total_voting_weight()).unwrap_json::<U128>().0, 0);
+
+    let current_timestamp = root.borrow_runtime().cur_block.block_timestamp;
+    call!(
+        user,
+        xref_contract.lock(to_yocto("4").into(), 1),
+        deposit = to_yocto("0.1")
+    )
+    .assert_success();
+    call!(
+        user,
+        xref_contract.lock(to_yocto("4").into(), 6),
+        deposit = to_yocto("0.1")
+    )
+    .assert_success();
+
+    let lock_info = view!(xref_contract.get_lock(user.valid_account_id())).unwrap_json::<Option<LockInfo>>().unwrap();
+    assert_eq!(lock_info.amount.0, to_yocto("8"));
+    assert!(lock_info.unlock_time_in_sec >= (nano_to_sec(current_timestamp) + 6 * 30 * DAY_IN_SEC) / DAY_IN_SEC * DAY_IN_SEC);
+    assert_eq!(
+        view!(xref_contract.get_total_voting_weight()).unwrap_json::<U128>().0,
+        lock_info.voting_weight.0
+    );
+
+    // a locked account can't unregister
+    let out_come = call!(user, xref_contract.storage_unregister(Some(true)), deposit = 1);
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_ACCOUNT_HAS_ACTIVE_LOCK"));
+}
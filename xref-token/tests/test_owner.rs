@@ -1,6 +1,7 @@
 use near_sdk_sim::{call, view, to_yocto};
 use xref_token::ContractMetadata;
-use near_sdk::json_types::U128;
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_contract_standards::fungible_token::metadata::FungibleTokenMetadata;
 
 mod common;
 use crate::common::{
@@ -35,13 +36,13 @@ fn test_reset_reward_genesis_time(){
     assert_eq!(U128(to_yocto("100")), xref_info.undistributed_reward);
     assert_eq!(U128(to_yocto("1")), xref_info.reward_per_sec);
     assert_eq!(U128(to_yocto("100")), xref_info.cur_undistributed_reward);
-    assert_eq!(U128(to_yocto("0")), xref_info.cur_locked_token_amount);
+    assert_eq!(U128(DEAD_SHARES), xref_info.cur_locked_token_amount);
 
     // and reward won't be distributed before genesis time
     root.borrow_runtime_mut().cur_block.block_timestamp = 100_000_000_000;
     let xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
     assert_eq!(U128(to_yocto("100")), xref_info.cur_undistributed_reward);
-    assert_eq!(U128(to_yocto("0")), xref_info.cur_locked_token_amount);
+    assert_eq!(U128(DEAD_SHARES), xref_info.cur_locked_token_amount);
 
     // and nothing happen even if some action invoke the reward distribution before genesis time
     call!(
@@ -55,7 +56,7 @@ fn test_reset_reward_genesis_time(){
     assert_eq!(U128(to_yocto("100")), xref_info.undistributed_reward);
     assert_eq!(U128(to_yocto("0.5")), xref_info.reward_per_sec);
     assert_eq!(U128(to_yocto("100")), xref_info.cur_undistributed_reward);
-    assert_eq!(U128(to_yocto("0")), xref_info.cur_locked_token_amount);
+    assert_eq!(U128(DEAD_SHARES), xref_info.cur_locked_token_amount);
     
     // change genesis time would also change prev_distribution_time_in_sec
     let current_timestamp = root.borrow_runtime().cur_block.block_timestamp;
@@ -69,13 +70,13 @@ fn test_reset_reward_genesis_time(){
     assert_eq!(U128(to_yocto("100")), xref_info.undistributed_reward);
     assert_eq!(U128(to_yocto("0.5")), xref_info.reward_per_sec);
     assert_eq!(U128(to_yocto("100")), xref_info.cur_undistributed_reward);
-    assert_eq!(U128(to_yocto("0")), xref_info.cur_locked_token_amount);
+    assert_eq!(U128(DEAD_SHARES), xref_info.cur_locked_token_amount);
 
     // when it past genesis time
     root.borrow_runtime_mut().cur_block.block_timestamp = current_timestamp + 60_000_000_000;
     let xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
     assert_eq!(U128(to_yocto("95")), xref_info.cur_undistributed_reward);
-    assert_eq!(U128(to_yocto("5")), xref_info.cur_locked_token_amount);
+    assert_eq!(U128(to_yocto("5") + DEAD_SHARES), xref_info.cur_locked_token_amount);
     // when some call invoke reward distribution after reward genesis time
     root.borrow_runtime_mut().cur_block.block_timestamp = current_timestamp + 70_000_000_000;
     call!(
@@ -88,10 +89,10 @@ fn test_reset_reward_genesis_time(){
     assert_eq!(xref_info.reward_genesis_time_in_sec, nano_to_sec(current_timestamp) + 50);
     assert_eq!(xref_info.prev_distribution_time_in_sec, nano_to_sec(current_timestamp) + 71);
     assert_eq!(U128(to_yocto("89.5")), xref_info.undistributed_reward);
-    assert_eq!(U128(to_yocto("10.5")), xref_info.locked_token_amount);
+    assert_eq!(U128(to_yocto("10.5") + DEAD_SHARES), xref_info.locked_token_amount);
     assert_eq!(U128(to_yocto("1")), xref_info.reward_per_sec);
     assert_eq!(U128(to_yocto("80.5")), xref_info.cur_undistributed_reward);
-    assert_eq!(U128(to_yocto("19.5")), xref_info.cur_locked_token_amount);
+    assert_eq!(U128(to_yocto("19.5") + DEAD_SHARES), xref_info.cur_locked_token_amount);
 }
 
 #[test]
@@ -145,4 +146,290 @@ fn test_modify_reward_per_sec(){
     .assert_success();
     let xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
     assert_eq!(xref_info.reward_per_sec.0, to_yocto("1"));
-}
\ No newline at end of file
+}
+#[test]
+fn test_min_residual_supply(){
+    let (_, owner, user, ref_contract, xref_contract) = 
+        init_env(true);
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+
+    call!(
+        owner,
+        xref_contract.set_min_residual_supply(U128(to_yocto("5") + DEAD_SHARES))
+    )
+    .assert_success();
+    let xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_eq!(xref_info.min_residual_supply.0, to_yocto("5") + DEAD_SHARES);
+
+    let out_come = call!(
+        user,
+        xref_contract.unstake(to_yocto("6").into()),
+        deposit = 1
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_KEEP_AT_LEAST_ONE_XREF"));
+
+    call!(
+        user,
+        xref_contract.unstake(to_yocto("5").into()),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(to_yocto("95"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+}
+
+#[test]
+fn test_wind_down(){
+    let (root, owner, user, ref_contract, xref_contract) = 
+        init_env(true);
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        owner,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "reward".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+
+    let out_come = call!(
+        user,
+        xref_contract.set_wind_down(true)
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    call!(
+        owner,
+        xref_contract.set_wind_down(true)
+    )
+    .assert_success();
+    let xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert!(xref_info.wind_down);
+
+    // nobody can stake in wind-down
+    let out_come = call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("1").into(), None, "".to_string()),
+        deposit = 1
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_WIND_DOWN"));
+
+    // residual is still kept while reward is not ended
+    call!(
+        owner,
+        xref_contract.set_min_residual_supply(to_yocto("11").into())
+    )
+    .assert_success();
+    let out_come = call!(
+        user,
+        xref_contract.unstake(to_yocto("10").into()),
+        deposit = 1
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_KEEP_AT_LEAST_ONE_XREF"));
+
+    // distribute all the reward
+    call!(
+        owner,
+        xref_contract.modify_reward_per_sec(to_yocto("1").into(), true)
+    )
+    .assert_success();
+    let current_timestamp = root.borrow_runtime().cur_block.block_timestamp;
+    call!(
+        owner,
+        xref_contract.reset_reward_genesis_time_in_sec(nano_to_sec(current_timestamp) + 1)
+    ).assert_success();
+    root.borrow_runtime_mut().cur_block.block_timestamp = current_timestamp + 100 * 1_000_000_000;
+
+    call!(
+        user,
+        xref_contract.unstake(to_yocto("10").into()),
+        deposit = 1
+    )
+    .assert_success();
+    // the dead shares keep their part of the reward
+    let unlocked = (U256::from(to_yocto("10")) * U256::from(to_yocto("20") + DEAD_SHARES) / U256::from(to_yocto("10") + DEAD_SHARES)).as_u128();
+    assert_eq!(to_yocto("90") + unlocked, view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+    let xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_xref(&xref_info, 0, to_yocto("20") + DEAD_SHARES - unlocked, DEAD_SHARES);
+}
+
+#[test]
+fn test_sweep_token(){
+    let (root, owner, user, ref_contract, xref_contract) =
+        init_env(true);
+    let other_contract = deploy_token(&root, "other", &xref_contract);
+    call!(user, other_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+    call!(owner, other_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+    call!(root, other_contract.mint(user.valid_account_id(), to_yocto("10").into())).assert_success();
+
+    // foreign token sent by plain transfer
+    call!(
+        user,
+        other_contract.ft_transfer(xref_contract.valid_account_id(), to_yocto("10").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+
+    let out_come = call!(
+        user,
+        xref_contract.sweep_token(other_contract.valid_account_id(), user.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    // xREF held by the contract itself is the dead shares
+    let out_come = call!(
+        owner,
+        xref_contract.sweep_token(xref_contract.valid_account_id(), owner.valid_account_id())
+    );
+    assert!(get_error_status(&out_come).contains("ERR_ILLEGAL_TOKEN"));
+
+    call!(
+        owner,
+        xref_contract.sweep_token(other_contract.valid_account_id(), owner.valid_account_id()),
+        gas = 100_000_000_000_000
+    )
+    .assert_success();
+    assert_eq!(view!(other_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0, to_yocto("10"));
+    assert_eq!(view!(other_contract.ft_balance_of(xref_contract.valid_account_id())).unwrap_json::<U128>().0, 0);
+
+    // only the surplus of locked token can be swept
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("20").into(), None, "reward".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        user,
+        ref_contract.ft_transfer(xref_contract.valid_account_id(), to_yocto("5").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    let owner_balance = view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0;
+    call!(
+        owner,
+        xref_contract.sweep_token(ref_contract.valid_account_id(), owner.valid_account_id()),
+        gas = 100_000_000_000_000
+    )
+    .assert_success();
+    assert_eq!(view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0, owner_balance + to_yocto("5"));
+    assert_eq!(view!(ref_contract.ft_balance_of(xref_contract.valid_account_id())).unwrap_json::<U128>().0, to_yocto("30") + DEAD_SHARES);
+
+    // and nothing is left to sweep
+    call!(
+        owner,
+        xref_contract.sweep_token(ref_contract.valid_account_id(), owner.valid_account_id()),
+        gas = 100_000_000_000_000
+    )
+    .assert_success();
+    assert_eq!(view!(ref_contract.ft_balance_of(xref_contract.valid_account_id())).unwrap_json::<U128>().0, to_yocto("30") + DEAD_SHARES);
+}
+
+#[test]
+fn test_reconcile(){
+    let (_, owner, user, ref_contract, xref_contract) =
+        init_env(true);
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        user,
+        ref_contract.ft_transfer(xref_contract.valid_account_id(), to_yocto("5").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+
+    // anyone can reconcile, but only owner can credit the surplus
+    call!(
+        user,
+        xref_contract.reconcile(None),
+        gas = 100_000_000_000_000
+    )
+    .assert_success();
+    let out_come = call!(
+        user,
+        xref_contract.reconcile(Some(true)),
+        gas = 100_000_000_000_000
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    let xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_xref(&xref_info, 0, to_yocto("10") + DEAD_SHARES, to_yocto("10") + DEAD_SHARES);
+
+    call!(
+        owner,
+        xref_contract.reconcile(Some(true)),
+        gas = 100_000_000_000_000
+    )
+    .assert_success();
+    let xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_xref(&xref_info, to_yocto("5"), to_yocto("10") + DEAD_SHARES, to_yocto("10") + DEAD_SHARES);
+
+    // nothing more to credit
+    call!(
+        owner,
+        xref_contract.reconcile(Some(true)),
+        gas = 100_000_000_000_000
+    )
+    .assert_success();
+    let xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_xref(&xref_info, to_yocto("5"), to_yocto("10") + DEAD_SHARES, to_yocto("10") + DEAD_SHARES);
+
+    // only owner can recover orphaned REF, and nothing is orphaned here
+    assert_eq!(xref_info.orphaned_token_amount.0, 0);
+    let out_come = call!(
+        user,
+        xref_contract.recover_orphaned_token(user.valid_account_id(), None)
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    let out_come = call!(
+        owner,
+        xref_contract.recover_orphaned_token(owner.valid_account_id(), None)
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ENOUGH_ORPHANED_TOKEN"));
+}
+
+#[test]
+fn test_metadata(){
+    let (_, owner, user, _, xref_contract) =
+        init_env(true);
+    let metadata = view!(xref_contract.ft_metadata()).unwrap_json::<FungibleTokenMetadata>();
+    assert_eq!(metadata.symbol, "xREF".to_string());
+    assert!(metadata.icon.unwrap().starts_with("data:image/svg+xml;base64,"));
+
+    let out_come = call!(user, xref_contract.set_icon(None));
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    call!(owner, xref_contract.set_icon(Some("data:image/svg+xml;base64,AAAA".to_string()))).assert_success();
+    let metadata = view!(xref_contract.ft_metadata()).unwrap_json::<FungibleTokenMetadata>();
+    assert_eq!(metadata.icon, Some("data:image/svg+xml;base64,AAAA".to_string()));
+
+    let reference = Some("https://example.com/xref.json".to_string());
+    let out_come = call!(owner, xref_contract.set_reference(reference.clone(), None));
+    assert!(get_error_status(&out_come).contains("ERR_REFERENCE_WITHOUT_HASH"));
+    let out_come = call!(owner, xref_contract.set_reference(reference.clone(), Some(Base64VecU8(vec![1; 31]))));
+    assert!(get_error_status(&out_come).contains("ERR_INVALID_REFERENCE_HASH"));
+    call!(owner, xref_contract.set_reference(reference.clone(), Some(Base64VecU8(vec![1; 32])))).assert_success();
+    let metadata = view!(xref_contract.ft_metadata()).unwrap_json::<FungibleTokenMetadata>();
+    assert_eq!(metadata.reference, reference);
+    assert_eq!(metadata.reference_hash.unwrap().0, vec![1; 32]);
+}
@@ -13,8 +13,8 @@ fn test_reward(){
     let (root, owner, user, ref_contract, xref_contract) = 
         init_env(true);
     let mut total_reward = 0;
-    let mut total_locked = 0;
-    let mut total_supply = 0;
+    let mut total_locked = DEAD_SHARES;
+    let mut total_supply = DEAD_SHARES;
 
     call!(
         owner,
@@ -34,36 +34,32 @@ fn test_reward(){
     
     root.borrow_runtime_mut().cur_block.block_timestamp = (nano_to_sec(current_timestamp) + 10) as u64 * 1_000_000_000;
 
-    //add reward trigger distribute_reward, just update prev_distribution_time
+    //stake at price 1, as there is no reward yet
     call!(
-        owner,
-        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("100").into(), None, "reward".to_string()),
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("11").into(), None, "".to_string()),
         deposit = 1
     )
     .assert_success();
-    total_reward += to_yocto("100");
+    total_locked += to_yocto("11");
+    total_supply += to_yocto("11");
 
     let xref_info0 = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
-    assert_xref(&xref_info0, to_yocto("100"), 0, 0);
+    assert_xref(&xref_info0, 0, total_locked, total_supply);
     assert_eq!(to_yocto("1"), xref_info0.reward_per_sec.0);
+    assert_eq!(to_yocto("89"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
 
-    
-    //stake trigger distribute_reward
+    //add reward trigger distribute_reward, nothing to distribute yet
     call!(
-        user,
-        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("11").into(), None, "".to_string()),
+        owner,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("100").into(), None, "reward".to_string()),
         deposit = 1
     )
     .assert_success();
-    total_locked += to_yocto("11");
-    total_supply += to_yocto("11");
+    total_reward += to_yocto("100");
 
     let xref_info1 = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
-    let time_diff = xref_info1.prev_distribution_time_in_sec - xref_info0.prev_distribution_time_in_sec;
-    total_reward -= time_diff as u128 * xref_info1.reward_per_sec.0;
-    total_locked += time_diff as u128 * xref_info1.reward_per_sec.0;
     assert_xref(&xref_info1, total_reward, total_locked, total_supply);
-    assert_eq!(to_yocto("89"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
 
     assert!(root.borrow_runtime_mut().produce_block().is_ok());
 
@@ -138,7 +134,7 @@ fn test_reward(){
     total_locked -= unlocked;
     total_supply -= to_yocto("10");
 
-    assert_eq!(to_yocto("1"), total_supply);
+    assert_eq!(to_yocto("1") + DEAD_SHARES, total_supply);
     assert_xref(&xref_info5, total_reward, total_locked, total_supply);
     assert_eq!(to_yocto("89") + unlocked, view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
 
@@ -189,8 +185,8 @@ fn test_no_reward_befroe_reset_reward_genesis_time(){
     let (root, owner, user, ref_contract, xref_contract) = 
         init_env(true);
     let mut total_reward = 0;
-    let mut total_locked = 0;
-    let mut total_supply = 0;
+    let mut total_locked = DEAD_SHARES;
+    let mut total_supply = DEAD_SHARES;
 
     call!(
         owner,
@@ -210,7 +206,7 @@ fn test_no_reward_befroe_reset_reward_genesis_time(){
     total_reward += to_yocto("100");
 
     let xref_info1 = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
-    assert_xref(&xref_info1, to_yocto("100"), 0, 0);
+    assert_xref(&xref_info1, to_yocto("100"), DEAD_SHARES, DEAD_SHARES);
     assert_eq!(to_yocto("1"), xref_info1.reward_per_sec.0);
 
     //stake trigger distribute_reward
@@ -250,7 +246,7 @@ fn test_no_reward_befroe_reset_reward_genesis_time(){
     assert_eq!(to_yocto("80"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
 
     assert_eq!(xref_info3.undistributed_reward.0, to_yocto("100"));
-    assert_eq!(xref_info3.locked_token_amount.0, to_yocto("20"));
+    assert_eq!(xref_info3.locked_token_amount.0, to_yocto("20") + DEAD_SHARES);
 
     assert!(root.borrow_runtime_mut().produce_blocks(10).is_ok());
 
@@ -271,12 +267,12 @@ fn test_no_reward_befroe_reset_reward_genesis_time(){
     total_locked -= unlocked;
     total_supply -= to_yocto("10");
 
-    assert_eq!(to_yocto("10"), total_locked);
-    assert_eq!(to_yocto("10"), total_supply);
+    assert_eq!(to_yocto("10") + DEAD_SHARES, total_locked);
+    assert_eq!(to_yocto("10") + DEAD_SHARES, total_supply);
     assert_xref(&xref_info4, total_reward, total_locked, total_supply);
     assert_eq!(to_yocto("80") + unlocked, view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
 
     assert_eq!(unlocked, to_yocto("10"));
     assert_eq!(xref_info4.undistributed_reward.0, to_yocto("100"));
-    assert_eq!(xref_info4.locked_token_amount.0, to_yocto("10"));
+    assert_eq!(xref_info4.locked_token_amount.0, to_yocto("10") + DEAD_SHARES);
 }
\ No newline at end of file
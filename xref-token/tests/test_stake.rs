@@ -21,7 +21,7 @@ fn test_stake(){
     .assert_success();
 
     let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
-    assert_xref(&current_xref_info, 0, to_yocto("10"), to_yocto("10"));
+    assert_xref(&current_xref_info, 0, to_yocto("10") + DEAD_SHARES, to_yocto("10") + DEAD_SHARES);
     assert_eq!(100000000_u128, view!(xref_contract.get_virtual_price()).unwrap_json::<U128>().0);
     assert_eq!(to_yocto("90"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
 }
@@ -42,7 +42,7 @@ fn test_stake_no_register(){
     assert_eq!(to_yocto("100"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
 
     let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
-    assert_xref(&current_xref_info, 0, 0, 0);
+    assert_xref(&current_xref_info, 0, DEAD_SHARES, DEAD_SHARES);
 }
 
 #[test]
@@ -61,5 +61,164 @@ fn test_stake_zero(){
     assert_eq!(to_yocto("100"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
 
     let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
-    assert_xref(&current_xref_info, 0, 0, 0);
-}
\ No newline at end of file
+    assert_xref(&current_xref_info, 0, DEAD_SHARES, DEAD_SHARES);
+}
+
+#[test]
+fn test_stake_caps(){
+    let (_, owner, user, ref_contract, xref_contract) =
+        init_env(true);
+    call!(owner, xref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+
+    call!(
+        owner,
+        xref_contract.set_stake_caps(Some(to_yocto("15").into()), Some(to_yocto("8").into()))
+    )
+    .assert_success();
+    let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_eq!(current_xref_info.max_locked_token_amount, Some(U128(to_yocto("15"))));
+    assert_eq!(current_xref_info.max_account_stake, Some(U128(to_yocto("8"))));
+
+    // account cap
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(to_yocto("92"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+    assert_eq!(to_yocto("8"), view!(xref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+
+    // total cap, which counts the dead shares
+    call!(
+        owner,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(to_yocto("9993"), view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0);
+    assert_eq!(to_yocto("7") - DEAD_SHARES, view!(xref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0);
+
+    // fully refunded when caps are reached
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("5").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(to_yocto("92"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+
+    let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_xref(&current_xref_info, 0, to_yocto("15"), to_yocto("15"));
+}
+
+#[test]
+fn test_stake_caps_min_stake(){
+    let (_, owner, user, ref_contract, xref_contract) =
+        init_env(true);
+    call!(
+        owner,
+        xref_contract.set_stake_caps(None, Some(to_yocto("8").into()))
+    )
+    .assert_success();
+    call!(
+        owner,
+        xref_contract.set_min_stake_amount(to_yocto("3").into())
+    )
+    .assert_success();
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("7").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(to_yocto("93"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+
+    // the minimum applies to the accepted part, not to the transferred amount
+    let out_come = call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("5").into(), None, "".to_string()),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_STAKE_BELOW_MINIMUM"));
+    assert_eq!(to_yocto("93"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+    assert_eq!(to_yocto("7"), view!(xref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+}
+
+#[test]
+fn test_batch_stake(){
+    let (root, owner, user, ref_contract, xref_contract) =
+        init_env(true);
+    let user2 = root.create_user("user2".to_string(), to_yocto("100"));
+    call!(user2, xref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+
+    // double the price by distributing as much reward as locked
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        owner,
+        xref_contract.modify_reward_per_sec(to_yocto("1").into(), true)
+    )
+    .assert_success();
+    let current_timestamp = root.borrow_runtime().cur_block.block_timestamp;
+    call!(
+        owner,
+        xref_contract.reset_reward_genesis_time_in_sec(nano_to_sec(current_timestamp) + 10)
+    ).assert_success();
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), U128(to_yocto("10") + DEAD_SHARES), None, "reward".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    root.borrow_runtime_mut().cur_block.block_timestamp = current_timestamp + 100 * 1_000_000_000;
+
+    let msg = format!(
+        "{{\"action\": \"batch_stake\", \"entries\": [[\"user\", \"{}\"], [\"user2\", \"{}\"], [\"nobody\", \"{}\"], [\"user\", \"1\"]]}}",
+        to_yocto("4"), to_yocto("6"), to_yocto("5")
+    );
+    // only owner can batch stake
+    let out_come = call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("15").into(), None, msg.clone()),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    // the entries should sum up to the transferred amount
+    let out_come = call!(
+        owner,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("16").into(), None, msg.clone()),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_BATCH_AMOUNT_MISMATCH"));
+    // and must not overflow to match it
+    let overflow_msg = format!(
+        "{{\"action\": \"batch_stake\", \"entries\": [[\"user\", \"{}\"], [\"user2\", \"2\"]]}}",
+        u128::MAX
+    );
+    let out_come = call!(
+        owner,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), U128(1), None, overflow_msg),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_BATCH_AMOUNT_MISMATCH"));
+
+    let owner_balance = view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0;
+    call!(
+        owner,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), U128(to_yocto("15") + 1), None, msg),
+        deposit = 1
+    )
+    .assert_success();
+    // the entries of unregistered account and too small to mint any XREF are refunded
+    assert_eq!(view!(ref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0, owner_balance - to_yocto("10"));
+    assert_eq!(view!(xref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0, to_yocto("12"));
+    assert_eq!(view!(xref_contract.ft_balance_of(user2.valid_account_id())).unwrap_json::<U128>().0, to_yocto("3"));
+    let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_xref(&current_xref_info, 0, to_yocto("30") + 2 * DEAD_SHARES, to_yocto("15") + DEAD_SHARES);
+}
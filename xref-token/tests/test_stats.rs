@@ -0,0 +1,79 @@
+use near_sdk_sim::{call, view, to_yocto};
+use xref_token::StatsInfo;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+#[test]
+fn test_stats(){
+    let (root, owner, user, ref_contract, xref_contract) =
+        init_env(true);
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "reward".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    call!(
+        user,
+        xref_contract.unstake(to_yocto("4").into()),
+        deposit = 1
+    )
+    .assert_success();
+    // a failed unstake doesn't count
+    let out_come = call!(
+        user,
+        xref_contract.unstake(to_yocto("100").into()),
+        deposit = 1
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+
+    let stats = view!(xref_contract.get_stats()).unwrap_json::<StatsInfo>();
+    // the dead shares count as a stake
+    assert_eq!(stats.total_staked.0, to_yocto("10") + DEAD_SHARES);
+    assert_eq!(stats.total_unstaked.0, to_yocto("4"));
+    assert_eq!(stats.total_reward_deposited.0, to_yocto("10"));
+    assert_eq!(stats.total_reward_distributed.0, 0);
+    assert_eq!(stats.stake_count, 2);
+    assert_eq!(stats.unstake_count, 1);
+
+    // distribute 0.01 REF per second after genesis
+    call!(
+        owner,
+        xref_contract.modify_reward_per_sec(to_yocto("0.01").into(), true)
+    )
+    .assert_success();
+    let current_timestamp = root.borrow_runtime().cur_block.block_timestamp;
+    call!(
+        owner,
+        xref_contract.reset_reward_genesis_time_in_sec(nano_to_sec(current_timestamp) + 10)
+    ).assert_success();
+    root.borrow_runtime_mut().cur_block.block_timestamp = current_timestamp + 100 * 1_000_000_000;
+
+    let stats = view!(xref_contract.get_stats()).unwrap_json::<StatsInfo>();
+    assert_eq!(stats.total_reward_distributed.0, to_yocto("0.9"));
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("2").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    let stats = view!(xref_contract.get_stats()).unwrap_json::<StatsInfo>();
+    assert_eq!(stats.total_staked.0, to_yocto("12") + DEAD_SHARES);
+    assert_eq!(stats.stake_count, 3);
+    // the stake took some blocks, during which the reward kept being distributed
+    let genesis_time_in_sec = nano_to_sec(current_timestamp) + 10;
+    let cur_time_in_sec = nano_to_sec(root.borrow_runtime().cur_block.block_timestamp);
+    assert_eq!(stats.total_reward_distributed.0, to_yocto("0.01") * (cur_time_in_sec - genesis_time_in_sec) as u128);
+}
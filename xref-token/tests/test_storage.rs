@@ -1,8 +1,12 @@
 use near_sdk_sim::{call, view, to_yocto};
-use xref_token::ContractMetadata;
+use xref_token::{ContractMetadata, SponsorshipInfo};
+use near_sdk::json_types::U128;
 
 mod common;
-use crate::common::init::*;
+use crate::common::{
+    init::*,
+    utils::*
+};
 
 #[test]
 fn test_account_number(){
@@ -23,4 +27,124 @@ fn test_account_number(){
     call!(user, xref_contract.storage_unregister(None), deposit = 1).assert_success();
     let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
     assert_eq!(current_xref_info.account_number, 0);
-}
\ No newline at end of file
+}
+#[test]
+fn test_holders(){
+    let (root, _, user, ref_contract, xref_contract) =
+        init_env(true);
+    let user2 = root.create_user("user2".to_string(), to_yocto("100"));
+    call!(user2, xref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+    // registering again doesn't duplicate the holder
+    call!(user2, xref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+
+    let accounts = view!(xref_contract.get_accounts(0, 10)).unwrap_json::<Vec<String>>();
+    // xref itself holds the dead shares
+    assert_eq!(accounts, vec!["user".to_string(), "xref".to_string(), "user2".to_string()]);
+    let accounts = view!(xref_contract.get_accounts(1, 10)).unwrap_json::<Vec<String>>();
+    assert_eq!(accounts, vec!["xref".to_string(), "user2".to_string()]);
+    let accounts = view!(xref_contract.get_accounts(2, 10)).unwrap_json::<Vec<String>>();
+    assert_eq!(accounts, vec!["user2".to_string()]);
+    let accounts = view!(xref_contract.get_accounts(3, 10)).unwrap_json::<Vec<String>>();
+    assert!(accounts.is_empty());
+    let accounts = view!(xref_contract.get_accounts(1, u64::MAX)).unwrap_json::<Vec<String>>();
+    assert_eq!(accounts, vec!["xref".to_string(), "user2".to_string()]);
+    let accounts = view!(xref_contract.get_accounts_with_balances(0, 1)).unwrap_json::<Vec<(String, U128)>>();
+    assert_eq!(accounts, vec![("user".to_string(), U128(to_yocto("10")))]);
+
+    call!(user2, xref_contract.storage_unregister(None), deposit = 1).assert_success();
+    let accounts = view!(xref_contract.get_accounts(0, 10)).unwrap_json::<Vec<String>>();
+    assert_eq!(accounts, vec!["user".to_string(), "xref".to_string()]);
+}
+
+#[test]
+fn test_sponsored_registration(){
+    let (root, owner, user, ref_contract, xref_contract) =
+        init_env(true);
+    let user2 = root.create_user("user2".to_string(), to_yocto("100"));
+    let user3 = root.create_user("user3".to_string(), to_yocto("100"));
+    for new_user in [&user2, &user3].iter() {
+        call!(new_user, ref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+        call!(root, ref_contract.mint(new_user.valid_account_id(), to_yocto("10").into())).assert_success();
+    }
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+
+    // not sponsored by default
+    let out_come = call!(
+        user2,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("1").into(), None, "".to_string()),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("Account not registered."));
+    assert_eq!(view!(ref_contract.ft_balance_of(user2.valid_account_id())).unwrap_json::<U128>().0, to_yocto("10"));
+
+    let out_come = call!(user, xref_contract.set_sponsored_registration(true, 1));
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+    call!(owner, xref_contract.set_sponsored_registration(true, 1)).assert_success();
+    call!(owner, xref_contract.fund_sponsor_pool(), deposit = to_yocto("1")).assert_success();
+    let sponsorship = view!(xref_contract.get_sponsorship()).unwrap_json::<SponsorshipInfo>();
+    assert_eq!(sponsorship.sponsor_pool.0, to_yocto("1"));
+    assert_eq!(sponsorship.remaining_accounts, 1);
+    let cost = sponsorship.cost_per_account.0;
+
+    call!(
+        user2,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("1").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(view!(xref_contract.ft_balance_of(user2.valid_account_id())).unwrap_json::<U128>().0, to_yocto("1"));
+    let sponsorship = view!(xref_contract.get_sponsorship()).unwrap_json::<SponsorshipInfo>();
+    assert_eq!(sponsorship.sponsor_pool.0, to_yocto("1") - cost);
+    assert_eq!(sponsorship.sponsored_account_number, 1);
+    assert_eq!(sponsorship.remaining_accounts, 0);
+    let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_eq!(current_xref_info.account_number, 2);
+
+    // over the cap, the stake is refunded
+    let out_come = call!(
+        user3,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("1").into(), None, "".to_string()),
+        deposit = 1
+    );
+    assert!(get_error_status(&out_come).contains("ERR_SPONSORED_ACCOUNTS_EXCEEDED"));
+    assert_eq!(view!(ref_contract.ft_balance_of(user3.valid_account_id())).unwrap_json::<U128>().0, to_yocto("10"));
+
+    // unregistering a sponsored account returns its fee to the pool
+    call!(user2, xref_contract.storage_unregister(Some(true)), deposit = 1).assert_success();
+    let sponsorship = view!(xref_contract.get_sponsorship()).unwrap_json::<SponsorshipInfo>();
+    assert_eq!(sponsorship.sponsor_pool.0, to_yocto("1"));
+    assert_eq!(sponsorship.sponsored_account_number, 0);
+    assert_eq!(sponsorship.remaining_accounts, 1);
+
+    // a stake refunded entirely by the caps takes no sponsorship
+    call!(owner, xref_contract.set_stake_caps(Some(U128(1)), None)).assert_success();
+    call!(
+        user3,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("1").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(view!(ref_contract.ft_balance_of(user3.valid_account_id())).unwrap_json::<U128>().0, to_yocto("10"));
+    let sponsorship = view!(xref_contract.get_sponsorship()).unwrap_json::<SponsorshipInfo>();
+    assert_eq!(sponsorship.sponsor_pool.0, to_yocto("1"));
+    assert_eq!(sponsorship.sponsored_account_number, 0);
+    call!(owner, xref_contract.set_stake_caps(None, None)).assert_success();
+
+    let owner_balance = owner.account().unwrap().amount;
+    call!(owner, xref_contract.withdraw_sponsor_pool(None)).assert_success();
+    assert!(owner.account().unwrap().amount > owner_balance + to_yocto("0.99"));
+    let sponsorship = view!(xref_contract.get_sponsorship()).unwrap_json::<SponsorshipInfo>();
+    assert_eq!(sponsorship.sponsor_pool.0, 0);
+    assert_eq!(sponsorship.remaining_accounts, 0);
+}
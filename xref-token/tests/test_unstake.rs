@@ -10,7 +10,7 @@ use crate::common::{
 
 #[test]
 fn test_unstake(){
-    let (_, _, user, ref_contract, xref_contract) = 
+    let (_, owner, user, ref_contract, xref_contract) = 
         init_env(true);
     
     call!(
@@ -22,7 +22,7 @@ fn test_unstake(){
 
     assert_eq!(to_yocto("90"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
     let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
-    assert_xref(&current_xref_info, 0, to_yocto("10"), to_yocto("10"));
+    assert_xref(&current_xref_info, 0, to_yocto("10") + DEAD_SHARES, to_yocto("10") + DEAD_SHARES);
     assert_eq!(100000000_u128, view!(xref_contract.get_virtual_price()).unwrap_json::<U128>().0);
 
     call!(
@@ -34,9 +34,15 @@ fn test_unstake(){
 
     assert_eq!(to_yocto("99"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
     let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
-    assert_xref(&current_xref_info, 0, to_yocto("1"), to_yocto("1"));
+    assert_xref(&current_xref_info, 0, to_yocto("1") + DEAD_SHARES, to_yocto("1") + DEAD_SHARES);
     assert_eq!(100000000_u128, view!(xref_contract.get_virtual_price()).unwrap_json::<U128>().0);
 
+    // the dead shares alone keep the default residual supply, so raise it above them
+    call!(
+        owner,
+        xref_contract.set_min_residual_supply((to_yocto("1") + DEAD_SHARES).into())
+    )
+    .assert_success();
     let out_come = call!(
         user,
         xref_contract.unstake(to_yocto("1").into()),
@@ -49,7 +55,7 @@ fn test_unstake(){
 #[test]
 fn test_unstake_empty_total_supply(){
     let (_, _, user, ref_contract, xref_contract) = 
-        init_env(true);
+        init_env_unseeded(true);
 
     let out_come = call!(
         user,
@@ -78,7 +84,7 @@ fn test_unstake_not_enough_balance(){
 
     assert_eq!(to_yocto("90"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
     let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
-    assert_xref(&current_xref_info, 0, to_yocto("10"), to_yocto("10"));
+    assert_xref(&current_xref_info, 0, to_yocto("10") + DEAD_SHARES, to_yocto("10") + DEAD_SHARES);
     assert_eq!(100000000_u128, view!(xref_contract.get_virtual_price()).unwrap_json::<U128>().0);
 
     let out_come = call!(
@@ -91,6 +97,66 @@ fn test_unstake_not_enough_balance(){
 
     assert_eq!(to_yocto("90"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
     let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
-    assert_xref(&current_xref_info, 0, to_yocto("10"), to_yocto("10"));
+    assert_xref(&current_xref_info, 0, to_yocto("10") + DEAD_SHARES, to_yocto("10") + DEAD_SHARES);
     assert_eq!(100000000_u128, view!(xref_contract.get_virtual_price()).unwrap_json::<U128>().0);
-}
\ No newline at end of file
+}
+#[test]
+fn test_unstake_call(){
+    let (root, owner, user, ref_contract, xref_contract) = 
+        init_env(true);
+    let xref2_contract = deploy_xref(&root, "xref2", &owner, &ref_contract);
+    call!(root, xref2_contract.storage_deposit(Some(xref_contract.valid_account_id()), None), deposit = to_yocto("1")).assert_success();
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+
+    // unlocked REF is staked into xref2 on behalf of xref
+    call!(
+        user,
+        xref_contract.unstake_call(to_yocto("4").into(), xref2_contract.valid_account_id(), "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+
+    assert_eq!(to_yocto("90"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+    assert_eq!(to_yocto("6"), view!(xref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+    assert_eq!(to_yocto("4"), view!(xref2_contract.ft_balance_of(xref_contract.valid_account_id())).unwrap_json::<U128>().0);
+    let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_xref(&current_xref_info, 0, to_yocto("6") + DEAD_SHARES, to_yocto("6") + DEAD_SHARES);
+    let current_xref2_info = view!(xref2_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_xref(&current_xref2_info, 0, to_yocto("4") + DEAD_SHARES, to_yocto("4") + DEAD_SHARES);
+}
+
+#[test]
+fn test_unstake_call_refund(){
+    let (root, owner, user, ref_contract, xref_contract) = 
+        init_env(true);
+    // xref is not registered in xref2, so xref2 would refuse the stake
+    let xref2_contract = deploy_xref(&root, "xref2", &owner, &ref_contract);
+
+    call!(
+        user,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("10").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+
+    call!(
+        user,
+        xref_contract.unstake_call(to_yocto("4").into(), xref2_contract.valid_account_id(), "".to_string()),
+        deposit = 1
+    );
+
+    // refund goes back to user as REF instead of re-minting XREF
+    assert_eq!(to_yocto("94"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+    assert_eq!(to_yocto("6"), view!(xref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+    assert_eq!(to_yocto("6") + DEAD_SHARES, view!(ref_contract.ft_balance_of(xref_contract.valid_account_id())).unwrap_json::<U128>().0);
+    let current_xref_info = view!(xref_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_xref(&current_xref_info, 0, to_yocto("6") + DEAD_SHARES, to_yocto("6") + DEAD_SHARES);
+    let current_xref2_info = view!(xref2_contract.contract_metadata()).unwrap_json::<ContractMetadata>();
+    assert_xref(&current_xref2_info, 0, DEAD_SHARES, DEAD_SHARES);
+}
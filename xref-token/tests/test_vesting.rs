@@ -0,0 +1,108 @@
+use near_sdk_sim::{call, view, to_yocto};
+use xref_token::VestingInfo;
+use near_sdk::json_types::U128;
+
+mod common;
+use crate::common::{
+    init::*,
+    utils::*
+};
+
+#[test]
+fn test_vesting(){
+    let (root, owner, user, ref_contract, xref_contract) =
+        init_env(true);
+
+    call!(owner, xref_contract.storage_deposit(None, None), deposit = to_yocto("1")).assert_success();
+    call!(
+        owner,
+        ref_contract.ft_transfer_call(xref_contract.valid_account_id(), to_yocto("100").into(), None, "".to_string()),
+        deposit = 1
+    )
+    .assert_success();
+
+    let current_sec = nano_to_sec(root.borrow_runtime().cur_block.block_timestamp);
+    let start = current_sec;
+    let cliff = current_sec + 100;
+    let end = current_sec + 1000;
+
+    // only owner can create vesting
+    let out_come = call!(
+        user,
+        xref_contract.create_vesting(user.valid_account_id(), start, cliff, end, to_yocto("10").into()),
+        deposit = to_yocto("0.1")
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ALLOWED"));
+
+    let out_come = call!(
+        owner,
+        xref_contract.create_vesting(user.valid_account_id(), cliff, start, end, to_yocto("10").into()),
+        deposit = to_yocto("0.1")
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_INVALID_VESTING_TIME"));
+
+    call!(
+        owner,
+        xref_contract.create_vesting(user.valid_account_id(), start, cliff, end, to_yocto("10").into()),
+        deposit = to_yocto("0.1")
+    )
+    .assert_success();
+    assert_eq!(to_yocto("90"), view!(xref_contract.ft_balance_of(owner.valid_account_id())).unwrap_json::<U128>().0);
+    assert_eq!(to_yocto("10"), view!(xref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+
+    let vesting = view!(xref_contract.get_vesting(user.valid_account_id())).unwrap_json::<Option<VestingInfo>>().unwrap();
+    assert_eq!(vesting.total_amount.0, to_yocto("10"));
+    assert_eq!(vesting.vested_amount.0, 0);
+    assert_eq!(vesting.unvested_amount.0, to_yocto("10"));
+
+    let out_come = call!(
+        owner,
+        xref_contract.create_vesting(user.valid_account_id(), start, cliff, end, to_yocto("10").into()),
+        deposit = to_yocto("0.1")
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_VESTING_EXISTS"));
+
+    // nothing can move before cliff
+    let out_come = call!(
+        user,
+        xref_contract.unstake(to_yocto("1").into()),
+        deposit = 1
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ENOUGH_UNLOCKED_BALANCE"));
+
+    // half vested
+    root.borrow_runtime_mut().cur_block.block_timestamp = (start + 500) as u64 * 1_000_000_000;
+    let vesting = view!(xref_contract.get_vesting(user.valid_account_id())).unwrap_json::<Option<VestingInfo>>().unwrap();
+    assert_eq!(vesting.vested_amount.0, to_yocto("5"));
+    assert_eq!(vesting.unvested_amount.0, to_yocto("5"));
+
+    let out_come = call!(
+        user,
+        xref_contract.ft_transfer(owner.valid_account_id(), to_yocto("6").into(), None),
+        deposit = 1
+    );
+    assert_eq!(get_error_count(&out_come), 1);
+    assert!(get_error_status(&out_come).contains("ERR_NOT_ENOUGH_UNLOCKED_BALANCE"));
+
+    call!(
+        user,
+        xref_contract.unstake(to_yocto("5").into()),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(to_yocto("105"), view!(ref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+
+    // fully vested
+    root.borrow_runtime_mut().cur_block.block_timestamp = end as u64 * 1_000_000_000;
+    call!(
+        user,
+        xref_contract.ft_transfer(owner.valid_account_id(), to_yocto("5").into(), None),
+        deposit = 1
+    )
+    .assert_success();
+    assert_eq!(0, view!(xref_contract.ft_balance_of(user.valid_account_id())).unwrap_json::<U128>().0);
+}